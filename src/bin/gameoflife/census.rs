@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use gameoflife::{Census, Cycle, CycleDetector, Rule, Seeding, Universe};
+
+/// Soups run unless `--soups` says otherwise.
+pub const SOUPS: usize = 100;
+/// Generations a soup may take to settle unless `--generations` says otherwise.
+pub const GENERATIONS: usize = 10_000;
+
+/// A batch of soups filled from consecutive RNG seeds.
+pub struct Soups {
+    pub seeding: Seeding,
+    pub density: f64,
+    pub first_seed: u64,
+    pub count: usize,
+}
+
+/// Seeds a universe made by `universe` for every soup, steps it until it settles into a cycle of
+/// at most `max_period` generations or `generations` generations have passed, and prints how
+/// each one ended, followed by a tally.
+///
+/// The ash of every soup that settled is separated into objects and counted, and the objects
+/// without a common name are written to `dump` as RLE files, if given.
+pub fn run(universe: impl Fn() -> Universe, soups: &Soups, generations: usize, max_period: usize, dump: Option<&str>) {
+    let start = Instant::now();
+    let mut outcomes: BTreeMap<String, usize> = BTreeMap::new();
+    let mut census = Census::new(max_period);
+    let mut rule = Rule::CONWAY;
+    let mut final_population = 0;
+    for seed in (0..soups.count as u64).map(|i| soups.first_seed.wrapping_add(i)) {
+        let mut universe = universe();
+        universe.seed_using(&soups.seeding, seed, soups.density);
+        let mut detector = CycleDetector::new(max_period);
+        let cycle = loop {
+            if let Some(cycle) = detector.observe(&universe) {
+                break Some(cycle);
+            }
+            if universe.generation() >= generations {
+                break None;
+            }
+            if let Err(error) = universe.step() {
+                crate::fail(format_args!("seed {}: {}", seed, error));
+            }
+        };
+
+        rule = universe.rule();
+        if cycle.is_some() {
+            census.add(&universe);
+        }
+        final_population += universe.population();
+        let outcome = cycle.as_ref().map_or_else(|| "still active".to_string(), Cycle::to_string);
+        println!(
+            "seed {:>20}  {:<32} generation {:>8}  population {:>8}",
+            seed,
+            outcome,
+            universe.generation(),
+            universe.population()
+        );
+        *outcomes.entry(outcome).or_insert(0) += 1;
+    }
+
+    println!();
+    for (outcome, count) in &outcomes {
+        println!("{:>8}  {}", count, outcome);
+    }
+    println!(
+        "{} soups in {:.2?}, average final population {:.1}",
+        soups.count,
+        start.elapsed(),
+        final_population as f64 / soups.count.max(1) as f64
+    );
+
+    print_census(&census, rule);
+    if let Some(dump) = dump {
+        dump_unnamed(&census, rule, dump);
+    }
+}
+
+/// Prints how often each object was seen, with its name if it has one.
+fn print_census(census: &Census, rule: Rule) {
+    if census.total() == 0 {
+        return;
+    }
+    println!();
+    println!("{} objects in the ash:", census.total());
+    for (object, count) in census.tally() {
+        let name = if rule == Rule::CONWAY { object.name() } else { None };
+        println!("{:>8}  {:<24} {}", count, object.code(), name.unwrap_or(""));
+    }
+}
+
+/// Writes the first sample of every object without a common name to `{code}.rle` in `dir`,
+/// noting the soup it was found in.
+fn dump_unnamed(census: &Census, rule: Rule, dir: &str) {
+    if let Err(error) = fs::create_dir_all(dir) {
+        eprintln!("Could not create {}: {}", dir, error);
+        return;
+    }
+    let mut dumped = 0;
+    for (object, _) in census.tally() {
+        if rule == Rule::CONWAY && object.name().is_some() {
+            continue;
+        }
+        let Some((_, seed)) = census.sample(object.code()) else {
+            continue;
+        };
+        let mut pattern = object.to_pattern().with_rule(rule);
+        if let Some(seed) = seed {
+            pattern = pattern.with_comment(format!("Found in the soup with seed {}", seed));
+        }
+        let path = Path::new(dir).join(format!("{}.rle", object.code()));
+        match pattern.save(&path) {
+            Ok(()) => dumped += 1,
+            Err(error) => eprintln!("Could not save {}: {}", path.display(), error),
+        }
+    }
+    println!("Dumped {} unnamed objects to {}", dumped, dir);
+}
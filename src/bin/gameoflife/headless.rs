@@ -0,0 +1,68 @@
+use std::time::Instant;
+
+use gameoflife::Universe;
+
+use crate::stats::StatsFile;
+use crate::watch::Watch;
+
+/// Generations between two progress lines unless `--report-every` says otherwise.
+pub const REPORT_EVERY: usize = 100;
+
+/// Steps the universe without a window until `watch` stops the run, `generations` generations
+/// have passed or the hashlife plane overflows, printing population stats along the way. Every
+/// generation is written to `stats` and the final board is saved to `output` as a pattern file,
+/// if given. Exits with an error status after an overflow.
+pub fn run(mut universe: Universe, mut watch: Watch, mut stats: Option<StatsFile>, generations: Option<usize>, report_every: usize, output: Option<&str>) {
+    let start = Instant::now();
+    let mut stepped = 0;
+    println!("generation {:>10}  population {:>10}", universe.generation(), universe.population());
+
+    if let Some(stats) = &mut stats {
+        stats.record(&universe);
+    }
+    let mut running = watch.check(&mut universe);
+    let mut overflowed = false;
+    while running && generations.is_none_or(|generations| stepped < generations) {
+        let previous = universe.generation();
+        if let Err(error) = universe.step() {
+            eprintln!("error: {}", error);
+            overflowed = true;
+            break;
+        }
+        stepped += universe.generation() - previous;
+        if let Some(stats) = &mut stats {
+            stats.record(&universe);
+        }
+        // the hashlife engine jumps many generations per step, so look for crossed multiples
+        if report_every > 0 && previous / report_every != universe.generation() / report_every {
+            println!("generation {:>10}  population {:>10}", universe.generation(), universe.population());
+        }
+        running = watch.check(&mut universe);
+    }
+
+    watch.print_tally();
+    let elapsed = start.elapsed();
+    println!("Stopped at generation {}, population {}", universe.generation(), universe.population());
+    println!(
+        "{} generations in {:.2?} ({:.1} generations/s)",
+        stepped,
+        elapsed,
+        stepped as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+    );
+    if let Some(stats) = stats {
+        stats.finish();
+    }
+
+    if let Some(path) = output {
+        match universe.to_pattern().save(path) {
+            Ok(()) => println!("Saved {}", path),
+            Err(error) => {
+                eprintln!("Could not save {}: {}", path, error);
+                std::process::exit(1);
+            }
+        }
+    }
+    if overflowed {
+        std::process::exit(1);
+    }
+}
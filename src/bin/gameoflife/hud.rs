@@ -0,0 +1,173 @@
+use std::time::{Duration, Instant};
+
+use gameoflife::Universe;
+
+/// How often the simulation and render rates are recomputed.
+const RATE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Window pixels per font pixel.
+const TEXT_SCALE: usize = 2;
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+/// Space around the text, and between the lines, in font pixels.
+const PADDING: usize = 2;
+const TEXT_COLOR: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+
+/// The heads-up display in the top-left corner of the window: generation, population, rule,
+/// speed, and how many generations are simulated and frames rendered per second.
+pub struct Hud {
+    visible: bool,
+    /// Start of the current rate interval, with the frames drawn and generation reached so far.
+    since: Instant,
+    frames: usize,
+    generation: usize,
+    simulation_rate: f64,
+    render_rate: f64,
+}
+
+impl Hud {
+    pub fn new(visible: bool) -> Self {
+        Hud {
+            visible,
+            since: Instant::now(),
+            frames: 0,
+            generation: 0,
+            simulation_rate: 0.0,
+            render_rate: 0.0,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Draws the display over the frame if it is visible. Must be called for every rendered
+    /// frame, visible or not, to keep the rates current. `speed` is the throttle in generations
+    /// per second, `None` meaning unlimited.
+    pub fn draw(&mut self, frame: &mut [u8], width: usize, universe: &Universe, speed: Option<f64>, paused: bool) {
+        self.frames += 1;
+        let elapsed = self.since.elapsed();
+        if elapsed >= RATE_INTERVAL {
+            // the generation restarts from 0 when a new soup is seeded
+            let generations = universe.generation().checked_sub(self.generation).unwrap_or(universe.generation());
+            self.simulation_rate = generations as f64 / elapsed.as_secs_f64();
+            self.render_rate = self.frames as f64 / elapsed.as_secs_f64();
+            self.since = Instant::now();
+            self.frames = 0;
+            self.generation = universe.generation();
+        }
+        if !self.visible {
+            return;
+        }
+
+        let speed = match (paused, speed) {
+            (true, _) => "PAUSED".to_string(),
+            (false, Some(speed)) => format!("SPEED {} GEN/S", speed),
+            (false, None) => "SPEED UNLIMITED".to_string(),
+        };
+        let lines = [
+            format!("GENERATION {}", universe.generation()),
+            format!("POPULATION {}", universe.population()),
+            format!("RULE {}", universe.rule()),
+            speed,
+            format!("SIM {:.0} GEN/S", self.simulation_rate),
+            format!("RENDER {:.0} FPS", self.render_rate),
+        ];
+
+        let line_height = GLYPH_HEIGHT + PADDING;
+        let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let box_width = (PADDING + columns * (GLYPH_WIDTH + 1) - 1 + PADDING) * TEXT_SCALE;
+        let box_height = (PADDING + lines.len() * line_height) * TEXT_SCALE;
+        darken(frame, width, box_width, box_height);
+        for (i, line) in lines.iter().enumerate() {
+            let y = (PADDING + i * line_height) * TEXT_SCALE;
+            draw_text(frame, width, PADDING * TEXT_SCALE, y, line);
+        }
+    }
+}
+
+/// Dims the `box_width` x `box_height` rectangle in the top-left corner so text stays readable.
+fn darken(frame: &mut [u8], width: usize, box_width: usize, box_height: usize) {
+    for row in frame.chunks_exact_mut(width * 4).take(box_height) {
+        for pixel in row.chunks_exact_mut(4).take(box_width) {
+            for channel in &mut pixel[..3] {
+                *channel /= 4;
+            }
+        }
+    }
+}
+
+/// Draws `text` with its top-left corner at pixel `(x, y)`, clipped to the frame. Letters are
+/// drawn in upper case, characters without a glyph as `?`.
+fn draw_text(frame: &mut [u8], width: usize, x: usize, y: usize, text: &str) {
+    let height = frame.len() / 4 / width.max(1);
+    for (i, c) in text.chars().enumerate() {
+        let left = x + i * (GLYPH_WIDTH + 1) * TEXT_SCALE;
+        for (row, bits) in glyph(c.to_ascii_uppercase()).into_iter().enumerate() {
+            for column in (0..GLYPH_WIDTH).filter(|column| bits >> (GLYPH_WIDTH - 1 - column) & 1 == 1) {
+                for dy in 0..TEXT_SCALE {
+                    for dx in 0..TEXT_SCALE {
+                        let (px, py) = (left + column * TEXT_SCALE + dx, y + row * TEXT_SCALE + dy);
+                        if px < width && py < height {
+                            frame[(py * width + px) * 4..(py * width + px + 1) * 4].copy_from_slice(&TEXT_COLOR);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The 5x7 bitmap of a character, one row per entry with the leftmost pixel as bit 4.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c {
+        ' ' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        '/' => [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000],
+        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+        ',' => [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000],
+        ':' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000],
+        '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+        '+' => [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000],
+        '=' => [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000],
+        '%' => [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011],
+        '(' => [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+        ')' => [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+        _ => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100],
+    }
+}
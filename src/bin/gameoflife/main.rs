@@ -0,0 +1,458 @@
+use std::fmt;
+use std::process;
+use std::thread;
+
+use clap::{Args, Parser, Subcommand};
+use gameoflife::cycle::MAX_PERIOD;
+use gameoflife::hashlife::MAX_STEP_LOG;
+use gameoflife::{Engine, Pattern, Rule, Seeding, Topology, Universe, DEFAULT_DENSITY, HEIGHT, WIDTH};
+#[cfg(feature = "window")]
+use gameoflife::{Coloring, Palette};
+use stats::StatsFile;
+use watch::{OnCycle, Watch};
+
+mod census;
+mod headless;
+#[cfg(feature = "window")]
+mod hud;
+mod stats;
+mod watch;
+#[cfg(feature = "window")]
+mod window;
+
+/// Number of patterns stamped by `--stamp` unless `--stamp-count` says otherwise.
+const STAMP_COUNT: usize = 10;
+/// Longest side a bounded board grows to when fitted to a loaded pattern.
+const MAX_FITTED_SIZE: usize = 4096;
+
+/// Conway's Game of Life and other life-like cellular automata.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Simulate in an interactive window
+    #[cfg(feature = "window")]
+    Run(RunArgs),
+    /// Simulate without a window, printing population stats
+    Headless(HeadlessArgs),
+    /// Convert a pattern or snapshot to another pattern format
+    Convert(ConvertArgs),
+    /// Run a batch of random soups and report how they end
+    Census(CensusArgs),
+}
+
+/// The board and how it is stepped.
+#[derive(Args)]
+#[command(next_help_heading = "Board")]
+struct BoardArgs {
+    /// Board width in cells; defaults to 640, or wider to fit a pattern
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    width: Option<usize>,
+    /// Board height in cells; defaults to 480, or taller to fit a pattern
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    height: Option<usize>,
+    /// Rule in B/S notation, e.g. B36/S23; defaults to the pattern's rule or B3/S23
+    #[arg(long)]
+    rule: Option<Rule>,
+    /// Simulation engine: dense, packed, hashlife or sparse
+    #[arg(long, default_value_t = Engine::Dense)]
+    engine: Engine,
+    /// Edge topology: torus, dead, reflect, klein or projective
+    #[arg(long, default_value_t = Topology::Torus)]
+    topology: Topology,
+    /// Worker threads; defaults to the number of cores
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Generations per hashlife step as a power of two
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(..=MAX_STEP_LOG as i64))]
+    step_log: u8,
+}
+
+impl BoardArgs {
+    fn threads(&self) -> usize {
+        self.threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+
+    /// An empty universe with these settings, stepping with `rule` unless `--rule` overrides it.
+    fn universe(&self, rule: Rule) -> Universe {
+        self.sized_universe(self.width.unwrap_or(WIDTH), self.height.unwrap_or(HEIGHT), rule)
+    }
+
+    fn sized_universe(&self, width: usize, height: usize, rule: Rule) -> Universe {
+        let rule = self.rule.unwrap_or(rule);
+        if rule.birth(0) && matches!(self.engine, Engine::HashLife | Engine::Sparse) {
+            fail(format_args!("the {} engine does not support rules with B0, which would fill the unbounded plane", self.engine));
+        }
+        Universe::new(width, height)
+            .with_rule(rule)
+            .with_topology(self.topology)
+            .with_threads(self.threads())
+            .with_step_log(self.step_log)
+            .with_engine(self.engine)
+    }
+}
+
+/// How random soups are filled.
+#[derive(Args)]
+#[command(next_help_heading = "Soup")]
+struct SoupArgs {
+    /// Probability of a cell being alive in random soups, from 0 to 1
+    #[arg(long, default_value_t = DEFAULT_DENSITY, value_parser = parse_density)]
+    density: f64,
+    /// Seeding strategy: uniform, box:<w>x<h>, c1|c2|c4|d2|d4|d8:<size> or perlin:<scale>
+    #[arg(long, conflicts_with = "stamp")]
+    soup: Option<Seeding>,
+    /// Stamp copies of these comma separated pattern files instead of filling with noise
+    #[arg(long, value_delimiter = ',')]
+    stamp: Vec<String>,
+    /// Number of patterns stamped by --stamp
+    #[arg(long, default_value_t = STAMP_COUNT)]
+    stamp_count: usize,
+}
+
+impl SoupArgs {
+    fn seeding(&self) -> Seeding {
+        if self.stamp.is_empty() {
+            return self.soup.clone().unwrap_or_default();
+        }
+        Seeding::Stamp {
+            patterns: self.stamp.iter().map(|path| load_pattern(path)).collect(),
+            count: self.stamp_count,
+        }
+    }
+}
+
+/// What the board starts with: a snapshot, a pattern or a random soup.
+#[derive(Args)]
+#[command(next_help_heading = "Start")]
+struct StartArgs {
+    /// Resume a session from a .golsnap snapshot; the board settings are taken from the file
+    #[arg(long, conflicts_with = "pattern")]
+    snapshot: Option<String>,
+    /// Start from a pattern file (.rle, .cells, .lif or .life)
+    #[arg(long)]
+    pattern: Option<String>,
+    /// Position of the pattern's top-left corner as <x>,<y>; defaults to centered
+    #[arg(long, value_parser = parse_position, requires = "pattern")]
+    at: Option<(usize, usize)>,
+    /// Seed for random soups; defaults to a random seed
+    #[arg(long)]
+    seed: Option<u64>,
+    #[command(flatten)]
+    soup: SoupArgs,
+}
+
+impl StartArgs {
+    fn universe(&self, board: &BoardArgs) -> Universe {
+        if let Some(path) = &self.snapshot {
+            let mut universe = Universe::load_snapshot(path).unwrap_or_else(|error| fail(format_args!("{}: {}", path, error)));
+            universe.set_threads(board.threads());
+            return universe;
+        }
+        if let Some(path) = &self.pattern {
+            let pattern = load_pattern(path);
+            // on the bounded engines a dimension left to its default grows to fit the pattern where
+            // it is placed; on the unbounded ones the board is only a window onto the plane
+            let bounded = matches!(board.engine, Engine::Dense | Engine::Packed);
+            let (at_x, at_y) = self.at.unwrap_or((0, 0));
+            let fit = |default: usize, extent: usize| if bounded { default.max(extent.min(MAX_FITTED_SIZE)) } else { default };
+            let width = board.width.unwrap_or_else(|| fit(WIDTH, at_x.saturating_add(pattern.width())));
+            let height = board.height.unwrap_or_else(|| fit(HEIGHT, at_y.saturating_add(pattern.height())));
+            let mut universe = board.sized_universe(width, height, pattern.rule().unwrap_or(Rule::CONWAY));
+            let (x, y) = self.at.unwrap_or((width.saturating_sub(pattern.width()) / 2, height.saturating_sub(pattern.height()) / 2));
+            if bounded && (x.saturating_add(pattern.width()) > width || y.saturating_add(pattern.height()) > height) {
+                eprintln!(
+                    "warning: the {}x{} pattern at {},{} does not fit the {}x{} board and is cut off",
+                    pattern.width(),
+                    pattern.height(),
+                    x,
+                    y,
+                    width,
+                    height
+                );
+            }
+            universe.place(&pattern, x, y);
+            return universe;
+        }
+
+        let mut universe = board.universe(Rule::CONWAY);
+        let seed = self.seed.unwrap_or_else(rand::random);
+        println!("Seed: {} (density {})", seed, self.soup.density);
+        universe.seed_using(&self.soup.seeding(), seed, self.soup.density);
+        universe
+    }
+}
+
+/// What happens once the board stops evolving, and soup farming.
+#[derive(Args)]
+#[command(next_help_heading = "Cycles and soup farming")]
+struct CycleArgs {
+    /// Action once the board settles into a still life or oscillator; defaults to report in the
+    /// window and to stop when headless
+    #[arg(long, value_enum)]
+    on_cycle: Option<OnCycle>,
+    /// Longest oscillator period to look for
+    #[arg(long, default_value_t = MAX_PERIOD)]
+    max_period: usize,
+    /// When reseeding, give up on a soup that is still evolving after this many generations
+    #[arg(long)]
+    give_up_after: Option<usize>,
+    /// Stop once this many soups have settled or been given up on
+    #[arg(long)]
+    soups: Option<usize>,
+    /// Append the outcome of every soup to this CSV file
+    #[arg(long)]
+    record: Option<String>,
+}
+
+impl CycleArgs {
+    fn watch(&self, on_cycle: OnCycle, soup: &SoupArgs) -> Watch {
+        let watch = Watch::new(self.max_period, self.on_cycle.unwrap_or(on_cycle), soup.seeding(), soup.density)
+            .with_give_up_after(self.give_up_after)
+            .with_soups(self.soups);
+        match &self.record {
+            Some(path) => watch.with_record(path).unwrap_or_else(|error| fail(format_args!("{}: {}", path, error))),
+            None => watch,
+        }
+    }
+}
+
+#[cfg(feature = "window")]
+#[derive(Args)]
+struct RunArgs {
+    /// Window pixels per cell, a power of two; zoom with the mouse wheel once running
+    #[arg(long, default_value_t = window::SCALE, value_parser = parse_scale)]
+    scale: u32,
+    /// Window title
+    #[arg(long, default_value = "Game of Life")]
+    title: String,
+    /// Generations per second; defaults to as fast as possible
+    #[arg(long)]
+    speed: Option<f64>,
+    /// Start paused
+    #[arg(long)]
+    paused: bool,
+    /// What colors live cells: fade, age, neighbors or changes; overrides the palette file
+    #[arg(long)]
+    coloring: Option<Coloring>,
+    /// Palette file with the colors and gradient to draw with
+    #[arg(long, value_name = "FILE")]
+    palette: Option<String>,
+    /// Write the population, births, deaths and bounding box of every generation to this file
+    /// (.csv or .json)
+    #[arg(long, value_name = "FILE")]
+    stats: Option<String>,
+    #[command(flatten)]
+    board: BoardArgs,
+    #[command(flatten)]
+    start: StartArgs,
+    #[command(flatten)]
+    cycles: CycleArgs,
+}
+
+#[cfg(feature = "window")]
+impl RunArgs {
+    fn settings(&self) -> window::Settings {
+        let mut palette = match &self.palette {
+            Some(path) => Palette::load(path).unwrap_or_else(|error| fail(format_args!("{}: {}", path, error))),
+            None => Palette::default(),
+        };
+        if let Some(coloring) = self.coloring {
+            palette.coloring = coloring;
+        }
+        window::Settings {
+            scale: self.scale,
+            title: self.title.clone(),
+            speed: self.speed,
+            paused: self.paused,
+            palette,
+        }
+    }
+}
+
+#[derive(Args)]
+struct HeadlessArgs {
+    /// Stop after this many generations instead of waiting for the board to stop evolving
+    #[arg(long)]
+    generations: Option<usize>,
+    /// Generations between two progress lines, 0 for none
+    #[arg(long, default_value_t = headless::REPORT_EVERY)]
+    report_every: usize,
+    /// Save the final board to this pattern file (.rle or .cells)
+    #[arg(long)]
+    output: Option<String>,
+    /// Write the population, births, deaths and bounding box of every generation to this file
+    /// (.csv or .json)
+    #[arg(long, value_name = "FILE")]
+    stats: Option<String>,
+    #[command(flatten)]
+    board: BoardArgs,
+    #[command(flatten)]
+    start: StartArgs,
+    #[command(flatten)]
+    cycles: CycleArgs,
+}
+
+#[derive(Args)]
+struct ConvertArgs {
+    /// Pattern (.rle, .cells, .lif, .life) or snapshot (.golsnap) to read
+    input: String,
+    /// Pattern file to write (.rle or .cells)
+    output: String,
+    /// Replace the pattern's rule
+    #[arg(long)]
+    rule: Option<Rule>,
+    /// Replace the pattern's name
+    #[arg(long)]
+    name: Option<String>,
+    /// Only convert the cells in this rectangle of the pattern or the snapshot's board, given as
+    /// <x>,<y>,<width>,<height>
+    #[arg(long, value_parser = parse_region)]
+    region: Option<(usize, usize, usize, usize)>,
+}
+
+#[derive(Args)]
+struct CensusArgs {
+    /// Number of soups to run
+    #[arg(long, default_value_t = census::SOUPS)]
+    soups: usize,
+    /// Seed of the first soup, the others use the following seeds; defaults to a random seed
+    #[arg(long)]
+    first_seed: Option<u64>,
+    /// Give up on a soup that is still evolving after this many generations
+    #[arg(long, default_value_t = census::GENERATIONS)]
+    generations: usize,
+    /// Longest oscillator period to look for
+    #[arg(long, default_value_t = MAX_PERIOD)]
+    max_period: usize,
+    /// Write the objects without a common name to this directory as RLE files
+    #[arg(long, value_name = "DIR")]
+    dump: Option<String>,
+    #[command(flatten)]
+    board: BoardArgs,
+    #[command(flatten)]
+    soup: SoupArgs,
+}
+
+fn main() {
+    // some thoughts...
+    // if this was about performance, we could do as follows:
+    // - only use 1/0 for alive/dead (no colors, grays...), 64 cells per u64 and two swapped
+    //   buffers: done, see `--engine packed`
+    // - split the work onto num_cores threads by splitting the rows (y) into num_cores bands:
+    //   done, see `--threads`. alternatively we could subdivide the playfield(screen) into quads,
+    //   which makes wrapping more difficult
+    // - write tests \o/ to figure out the smartest and best algorithm
+    // - order if-else statements by amount of instructions, e.g. == vs > x-1.
+    //   or use bitmasks...
+    // - inline functions
+    // - avoid if-statements and math if possible -> use bit fields, xor, or, etc.
+
+    match Cli::parse().command {
+        #[cfg(feature = "window")]
+        Command::Run(args) => {
+            let settings = args.settings();
+            let universe = args.start.universe(&args.board);
+            let watch = args.cycles.watch(OnCycle::Report, &args.start.soup);
+            let stats = args.stats.as_deref().map(create_stats_file);
+            window::run(universe, watch, stats, settings);
+        }
+        Command::Headless(args) => {
+            let universe = args.start.universe(&args.board);
+            let watch = args.cycles.watch(OnCycle::Stop, &args.start.soup);
+            let stats = args.stats.as_deref().map(create_stats_file);
+            headless::run(universe, watch, stats, args.generations, args.report_every, args.output.as_deref());
+        }
+        Command::Convert(args) => convert(&args),
+        Command::Census(args) => {
+            let first_seed = args.first_seed.unwrap_or_else(rand::random);
+            let soups = census::Soups {
+                seeding: args.soup.seeding(),
+                density: args.soup.density,
+                first_seed,
+                count: args.soups,
+            };
+            census::run(|| args.board.universe(Rule::CONWAY), &soups, args.generations, args.max_period, args.dump.as_deref());
+        }
+    }
+}
+
+/// Reads a pattern or the board of a snapshot and writes it in the format of the output file.
+fn convert(args: &ConvertArgs) {
+    let mut pattern = if args.input.ends_with(".golsnap") {
+        let universe = Universe::load_snapshot(&args.input).unwrap_or_else(|error| fail(format_args!("{}: {}", args.input, error)));
+        match args.region {
+            Some((x, y, width, height)) => universe.extract(x, y, width, height),
+            None => universe.to_pattern(),
+        }
+    } else {
+        let pattern = load_pattern(&args.input);
+        match args.region {
+            Some((x, y, width, height)) => pattern.crop(x, y, width, height),
+            None => pattern,
+        }
+    };
+    if let Some(rule) = args.rule {
+        pattern = pattern.with_rule(rule);
+    }
+    if let Some(name) = &args.name {
+        pattern = pattern.with_name(name);
+    }
+    pattern.save(&args.output).unwrap_or_else(|error| fail(format_args!("{}: {}", args.output, error)));
+    println!("Converted {} to {} ({} cells)", args.input, args.output, pattern.population());
+}
+
+fn create_stats_file(path: &str) -> StatsFile {
+    StatsFile::create(path).unwrap_or_else(|error| fail(format_args!("{}: {}", path, error)))
+}
+
+fn load_pattern(path: &str) -> Pattern {
+    Pattern::load(path).unwrap_or_else(|error| fail(format_args!("{}: {}", path, error)))
+}
+
+/// Parses a `<x>,<y>` board position.
+fn parse_position(position: &str) -> Result<(usize, usize), String> {
+    let parse = || {
+        let (x, y) = position.split_once(',')?;
+        Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+    };
+    parse().ok_or_else(|| format!("expected <x>,<y>, got '{}'", position))
+}
+
+/// Parses a rectangle given as `<x>,<y>,<width>,<height>` with a width and height of at least 1.
+fn parse_region(region: &str) -> Result<(usize, usize, usize, usize), String> {
+    let values: Vec<usize> = region.split(',').map_while(|value| value.trim().parse().ok()).collect();
+    match values[..] {
+        [x, y, width, height] if width > 0 && height > 0 => Ok((x, y, width, height)),
+        _ => Err(format!("expected <x>,<y>,<width>,<height>, got '{}'", region)),
+    }
+}
+
+/// Parses a soup density, which must be a probability.
+fn parse_density(density: &str) -> Result<f64, String> {
+    match density.parse::<f64>() {
+        Ok(value) if (0.0..=1.0).contains(&value) => Ok(value),
+        _ => Err(format!("expected a number from 0 to 1, got '{}'", density)),
+    }
+}
+
+/// Parses a window scale, which must be a power of two the camera can zoom to.
+#[cfg(feature = "window")]
+fn parse_scale(scale: &str) -> Result<u32, String> {
+    let max = 1 << gameoflife::render::MAX_ZOOM;
+    match scale.parse::<u32>() {
+        Ok(scale) if scale.is_power_of_two() && scale <= max => Ok(scale),
+        _ => Err(format!("expected a power of two up to {}, got '{}'", max, scale)),
+    }
+}
+
+/// Prints an error and exits.
+fn fail(message: impl fmt::Display) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1)
+}
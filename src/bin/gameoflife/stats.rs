@@ -0,0 +1,43 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+
+use gameoflife::{StatsWriter, Universe};
+
+/// The `--stats` file, receiving the statistics of every generation as the run goes.
+pub struct StatsFile {
+    path: String,
+    writer: Option<StatsWriter<BufWriter<File>>>,
+}
+
+impl StatsFile {
+    /// Creates the file, as JSON if its name ends in `.json` and as CSV otherwise.
+    pub fn create(path: &str) -> io::Result<Self> {
+        Ok(StatsFile {
+            path: path.to_string(),
+            writer: Some(StatsWriter::create(path)?),
+        })
+    }
+
+    /// Appends the current generation. After a write error the file is left alone.
+    pub fn record(&mut self, universe: &Universe) {
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+        if let Err(error) = writer.write(&universe.stats()) {
+            eprintln!("Could not write {}: {}", self.path, error);
+            self.writer = None;
+        }
+    }
+
+    /// Completes the file and reports how many generations it holds.
+    pub fn finish(self) {
+        let Some(writer) = self.writer else {
+            return;
+        };
+        let written = writer.written();
+        match writer.finish() {
+            Ok(_) => println!("Wrote statistics of {} generations to {}", written, self.path),
+            Err(error) => eprintln!("Could not write {}: {}", self.path, error),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use gameoflife::{Cycle, CycleDetector, Seeding, Universe};
+
+/// What to do once the board settles into a still life or an oscillator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OnCycle {
+    /// Report the cycle and keep going
+    Report,
+    /// Report the cycle and stop: pause the window or end the headless run
+    Stop,
+    /// Report the cycle and start a new soup from the next seed
+    Reseed,
+}
+
+/// Watches a running universe for cycles, reporting each one once and applying `--on-cycle`.
+///
+/// When reseeding it farms soups: each soup ends when it settles or runs out of `give_up_after`
+/// generations, its outcome is tallied and optionally appended to a CSV file, and the next soup
+/// is seeded until `soups` soups are done.
+pub struct Watch {
+    detector: CycleDetector,
+    on_cycle: OnCycle,
+    seeding: Seeding,
+    density: f64,
+    give_up_after: Option<usize>,
+    soups: Option<usize>,
+    record: Option<File>,
+    outcomes: BTreeMap<String, usize>,
+}
+
+impl Watch {
+    /// `seeding` and `density` are used to fill the board again when reseeding.
+    pub fn new(max_period: usize, on_cycle: OnCycle, seeding: Seeding, density: f64) -> Self {
+        Watch {
+            detector: CycleDetector::new(max_period),
+            on_cycle,
+            seeding,
+            density,
+            give_up_after: None,
+            soups: None,
+            record: None,
+            outcomes: BTreeMap::new(),
+        }
+    }
+
+    /// Moves on to the next soup when one is still evolving after `generations` generations.
+    /// Only used when reseeding.
+    pub fn with_give_up_after(mut self, generations: Option<usize>) -> Self {
+        self.give_up_after = generations;
+        self
+    }
+
+    /// Stops after `soups` soups have ended.
+    pub fn with_soups(mut self, soups: Option<usize>) -> Self {
+        self.soups = soups;
+        self
+    }
+
+    /// Appends the outcome of every soup to a CSV file, writing a header if the file is new.
+    pub fn with_record(mut self, path: &str) -> io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "seed,density,rule,outcome,period,generation,population")?;
+        }
+        self.record = Some(file);
+        Ok(self)
+    }
+
+    /// Checks the universe after a step. Returns `false` if the run should stop.
+    pub fn check(&mut self, universe: &mut Universe) -> bool {
+        let cycle = match self.detector.observe(universe) {
+            Some(cycle) => Some(cycle),
+            None if self.on_cycle == OnCycle::Reseed && self.give_up_after.is_some_and(|limit| universe.generation() >= limit) => None,
+            None => return true,
+        };
+        match cycle {
+            Some(cycle) => println!("Generation {}: {}, population {}", universe.generation(), cycle, universe.population()),
+            None => println!("Generation {}: still active, population {}, giving up", universe.generation(), universe.population()),
+        }
+        self.record(universe, cycle);
+        if self.soups.is_some_and(|soups| self.outcomes.values().sum::<usize>() >= soups) {
+            return false;
+        }
+
+        match self.on_cycle {
+            OnCycle::Report => true,
+            OnCycle::Stop => false,
+            OnCycle::Reseed => {
+                let seed = universe.rng_seed().map_or_else(rand::random, |seed| seed.wrapping_add(1));
+                println!("Seed: {} (density {})", seed, self.density);
+                universe.seed_using(&self.seeding, seed, self.density);
+                self.detector.clear();
+                true
+            }
+        }
+    }
+
+    /// Tallies how a soup ended, `None` meaning it was given up on, and appends it to the record.
+    fn record(&mut self, universe: &Universe, cycle: Option<Cycle>) {
+        let outcome = match cycle {
+            Some(Cycle::Extinct) => "extinct",
+            Some(Cycle::StillLife) => "still life",
+            Some(Cycle::Oscillator { .. }) => "oscillator",
+            None => "still active",
+        };
+        *self.outcomes.entry(cycle.map_or_else(|| outcome.to_string(), |cycle| cycle.to_string())).or_insert(0) += 1;
+
+        let Some(file) = &mut self.record else {
+            return;
+        };
+        let period = match cycle {
+            Some(Cycle::Oscillator { period }) => period,
+            Some(_) => 1,
+            None => 0,
+        };
+        let seed = universe.rng_seed().map_or_else(String::new, |seed| seed.to_string());
+        let line = format!(
+            "{},{},{},{},{},{},{}",
+            seed,
+            self.density,
+            universe.rule(),
+            outcome,
+            period,
+            universe.generation(),
+            universe.population()
+        );
+        if let Err(error) = writeln!(file, "{}", line) {
+            eprintln!("Could not record outcome: {}", error);
+        }
+    }
+
+    /// Prints how many soups ended in which way, if any did.
+    pub fn print_tally(&self) {
+        if self.outcomes.is_empty() {
+            return;
+        }
+        println!();
+        for (outcome, count) in &self.outcomes {
+            println!("{:>8}  {}", count, outcome);
+        }
+    }
+}
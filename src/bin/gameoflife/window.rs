@@ -0,0 +1,505 @@
+use std::time::{Duration, Instant};
+
+use gameoflife::{render, Camera, History, Palette, Universe};
+use pixels::{Pixels, SurfaceTexture};
+use winit::dpi::PhysicalSize;
+use winit::event::{Event, VirtualKeyCode};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::window::WindowBuilder;
+use winit_input_helper::WinitInputHelper;
+
+use crate::hud::Hud;
+use crate::stats::StatsFile;
+use crate::watch::Watch;
+
+/// Window pixels per cell unless `--scale` says otherwise.
+pub const SCALE: u32 = 2;
+
+/// Largest initial window; bigger boards start with the camera on their center.
+const MAX_WINDOW_SIZE: (u32, u32) = (1280, 960);
+
+/// Number of pixels the arrow keys move the picture by.
+const PAN_STEP: f64 = 64.0;
+
+/// Generations per second after slowing down from unlimited speed.
+const DEFAULT_SPEED: f64 = 64.0;
+/// Slowest and fastest throttled speeds; speeding up beyond `MAX_SPEED` removes the throttle.
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 8192.0;
+/// Time spent stepping per frame when unthrottled, so the window stays responsive.
+const FRAME_BUDGET: Duration = Duration::from_millis(15);
+/// How far a throttled simulation may fall behind before it gives up catching up.
+const MAX_LAG: f64 = 0.25;
+
+/// Generations kept for the graph, one pixel column each.
+const GRAPH_SAMPLES: usize = 2048;
+/// Height of the graph in pixels.
+const GRAPH_HEIGHT: usize = 96;
+const POPULATION_COLOR: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+const ACTIVITY_COLOR: [u8; 4] = [0xff, 0x90, 0x20, 0xff];
+/// Outline of the selected region.
+const SELECTION_COLOR: [u8; 4] = [0x40, 0xa0, 0xff, 0xff];
+
+const DIGITS: [(VirtualKeyCode, usize); 20] = [
+    (VirtualKeyCode::Key0, 0),
+    (VirtualKeyCode::Key1, 1),
+    (VirtualKeyCode::Key2, 2),
+    (VirtualKeyCode::Key3, 3),
+    (VirtualKeyCode::Key4, 4),
+    (VirtualKeyCode::Key5, 5),
+    (VirtualKeyCode::Key6, 6),
+    (VirtualKeyCode::Key7, 7),
+    (VirtualKeyCode::Key8, 8),
+    (VirtualKeyCode::Key9, 9),
+    (VirtualKeyCode::Numpad0, 0),
+    (VirtualKeyCode::Numpad1, 1),
+    (VirtualKeyCode::Numpad2, 2),
+    (VirtualKeyCode::Numpad3, 3),
+    (VirtualKeyCode::Numpad4, 4),
+    (VirtualKeyCode::Numpad5, 5),
+    (VirtualKeyCode::Numpad6, 6),
+    (VirtualKeyCode::Numpad7, 7),
+    (VirtualKeyCode::Numpad8, 8),
+    (VirtualKeyCode::Numpad9, 9),
+];
+
+/// How the window starts out.
+pub struct Settings {
+    /// Window pixels per cell, a power of two.
+    pub scale: u32,
+    pub title: String,
+    /// Generations per second, `None` for as fast as possible.
+    pub speed: Option<f64>,
+    pub paused: bool,
+    pub palette: Palette,
+}
+
+/// Opens a window showing every cell as a `scale` x `scale` block, colored with the palette,
+/// and steps the universe until the window is closed, at most `speed` generations per second if
+/// given.
+///
+/// Space pauses and resumes, N steps a single generation and typing a number before N steps that
+/// many generations. Plus and minus double and halve the speed. The mouse wheel zooms, and
+/// dragging with the middle button or with shift held pans, as do the arrow keys. G shows a graph
+/// of the population and activity of the generations stepped while it is shown, and H hides and
+/// shows the heads-up display. P switches to the next coloring. S and C save the board as RLE
+/// and plaintext, or only the region of the board selected by dragging with control held; X
+/// clears the selection. Every generation is written to `stats`, if given.
+pub fn run(mut universe: Universe, mut watch: Watch, mut stats: Option<StatsFile>, settings: Settings) -> ! {
+    let Settings { scale, title, speed, paused, mut palette } = settings;
+    let (width, height) = (universe.width(), universe.height());
+    let mut clock = Clock::new(speed.filter(|&speed| speed > 0.0), paused);
+    let mut count: Option<usize> = None;
+    let mut last_drawn: Option<(i64, i64)> = None;
+    // corner the selection was started from and the one currently dragged
+    let mut selection: Option<((i64, i64), (i64, i64))> = None;
+    let mut graph: Option<History> = None;
+    let mut hud = Hud::new(true);
+    universe.set_track_ages(palette.coloring.needs_ages());
+    println!("Left click draws, right click erases, control drag selects, wheel zooms, middle or shift drag pans, Space pauses, N steps, <count> N steps <count> generations, +/- change speed, G shows a graph, H toggles the HUD, P changes colors, S/C save a pattern or the selection, X clears the selection, F5 saves a snapshot, arrows pan, Esc quits");
+
+    if let Some(stats) = &mut stats {
+        stats.record(&universe);
+    }
+    watch.check(&mut universe);
+
+    let event_loop = EventLoop::new();
+    let mut input = WinitInputHelper::new();
+    let window_size = (
+        (width as u32).saturating_mul(scale).min(MAX_WINDOW_SIZE.0),
+        (height as u32).saturating_mul(scale).min(MAX_WINDOW_SIZE.1),
+    );
+    let window = WindowBuilder::new()
+        .with_title(&title)
+        .with_inner_size(PhysicalSize::new(window_size.0, window_size.1))
+        .build(&event_loop)
+        .unwrap();
+    // the frame buffer matches the window pixel for pixel, the camera does the scaling
+    let mut frame_width = window.inner_size().width as usize;
+    let mut pixels = {
+        let window_size = window.inner_size();
+        let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
+        Pixels::new(window_size.width, window_size.height, surface_texture).unwrap()
+    };
+    let mut camera = {
+        let scale = scale as f64;
+        let x = (width as f64 - window_size.0 as f64 / scale) / 2.0;
+        let y = (height as f64 - window_size.1 as f64 / scale) / 2.0;
+        Camera::new(x, y, scale.log2() as i32)
+    };
+
+    event_loop.run(move |event, _, control_flow| {
+        if let Event::RedrawRequested(_) = event {
+            render(&universe, &camera, &palette, pixels.get_frame(), frame_width);
+            if let Some(region) = selection.and_then(|selection| board_region(selection, &universe)) {
+                draw_selection(pixels.get_frame(), frame_width, &camera, region);
+            }
+            if let Some(history) = &graph {
+                draw_graph(pixels.get_frame(), frame_width, history);
+            }
+            hud.draw(pixels.get_frame(), frame_width, &universe, clock.speed, clock.paused);
+            if pixels
+                .render()
+                .is_err()
+            {
+                if let Some(stats) = stats.take() {
+                    stats.finish();
+                }
+                *control_flow = ControlFlow::Exit;
+                return;
+            }
+        }
+
+        // Handle input events
+        if input.update(&event) {
+            // Close events
+            if input.key_pressed(VirtualKeyCode::Escape) || input.quit() {
+                watch.print_tally();
+                if let Some(stats) = stats.take() {
+                    stats.finish();
+                }
+                *control_flow = ControlFlow::Exit;
+                return;
+            }
+
+            // Resize the window
+            if let Some(size) = input.window_resized() {
+                pixels.resize_surface(size.width, size.height);
+                if size.width > 0 && size.height > 0 {
+                    pixels.resize_buffer(size.width, size.height);
+                    frame_width = size.width as usize;
+                }
+            }
+
+            // Save the board or the selected region as a pattern file
+            for (key, extension) in [(VirtualKeyCode::S, "rle"), (VirtualKeyCode::C, "cells")] {
+                if input.key_pressed(key) {
+                    let path = format!("generation-{}.{}", universe.generation(), extension);
+                    let pattern = match selection {
+                        Some(selection) => match board_region(selection, &universe) {
+                            Some((x, y, width, height)) => universe.extract(x, y, width, height),
+                            None => {
+                                eprintln!("The selection lies outside the board");
+                                continue;
+                            }
+                        },
+                        None => universe.to_pattern(),
+                    };
+                    match pattern.save(&path) {
+                        Ok(()) => println!("Saved {}", path),
+                        Err(error) => eprintln!("Could not save {}: {}", path, error),
+                    }
+                }
+            }
+
+            // Checkpoint the whole session
+            if input.key_pressed(VirtualKeyCode::F5) {
+                let path = format!("generation-{}.golsnap", universe.generation());
+                match universe.save_snapshot(&path) {
+                    Ok(()) => println!("Saved snapshot {}", path),
+                    Err(error) => eprintln!("Could not save {}: {}", path, error),
+                }
+            }
+
+            // Pan with the arrow keys or by dragging, zoom around the cursor with the wheel
+            let pan = [
+                (VirtualKeyCode::Left, (PAN_STEP, 0.0)),
+                (VirtualKeyCode::Right, (-PAN_STEP, 0.0)),
+                (VirtualKeyCode::Up, (0.0, PAN_STEP)),
+                (VirtualKeyCode::Down, (0.0, -PAN_STEP)),
+            ];
+            for (key, (dx, dy)) in pan {
+                if input.key_pressed(key) {
+                    camera.pan(dx, dy);
+                }
+            }
+            let dragging = input.mouse_held(2) || (input.held_shift() && input.mouse_held(0));
+            if dragging {
+                let (dx, dy) = input.mouse_diff();
+                camera.pan(dx as f64, dy as f64);
+            }
+            let scroll = input.scroll_diff();
+            if scroll != 0.0 {
+                let size = window.inner_size();
+                let (x, y) = input.mouse().unwrap_or((size.width as f32 / 2.0, size.height as f32 / 2.0));
+                camera.zoom_at(x as f64, y as f64, scroll.signum() as i32);
+            }
+
+            // Select a region by dragging with control held
+            let selecting = input.held_control() && input.mouse_held(0) && !dragging;
+            if let (true, Some((x, y))) = (selecting, input.mouse()) {
+                let cell = camera.cell_at(x as f64, y as f64);
+                selection = match selection {
+                    Some((anchor, _)) if !input.mouse_pressed(0) => Some((anchor, cell)),
+                    _ => Some((cell, cell)),
+                };
+            }
+            if input.key_pressed(VirtualKeyCode::X) {
+                selection = None;
+            }
+
+            // Draw with the left button and erase with the right one, joining the cells visited
+            // between two frames so fast strokes stay connected
+            let button = [0, 1].into_iter().find(|&button| input.mouse_held(button));
+            match (button, input.mouse()) {
+                (Some(button), Some((x, y))) if !dragging && !selecting => {
+                    let cell = camera.cell_at(x as f64, y as f64);
+                    for (x, y) in line(last_drawn.unwrap_or(cell), cell) {
+                        paint(&mut universe, x, y, button == 0);
+                    }
+                    last_drawn = Some(cell);
+                }
+                _ => last_drawn = None,
+            }
+
+            // Show or hide the graph, which starts empty every time
+            if input.key_pressed(VirtualKeyCode::G) {
+                graph = match graph {
+                    Some(_) => None,
+                    None => Some(History::new(GRAPH_SAMPLES)),
+                };
+            }
+
+            if input.key_pressed(VirtualKeyCode::H) {
+                hud.toggle();
+            }
+
+            // Switch colorings, tracking cell ages only while they are needed
+            if input.key_pressed(VirtualKeyCode::P) {
+                palette.coloring = palette.coloring.next();
+                universe.set_track_ages(palette.coloring.needs_ages());
+                println!("Coloring: {}", palette.coloring);
+            }
+
+            // Pause, resume and single-step
+            if input.key_pressed(VirtualKeyCode::Space) {
+                clock.set_paused(!clock.paused);
+                println!("{}", if clock.paused { "Paused" } else { "Resumed" });
+            }
+            for (key, digit) in DIGITS {
+                if input.key_pressed(key) {
+                    count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                }
+            }
+            if input.key_pressed(VirtualKeyCode::N) {
+                clock.set_paused(true);
+                let mut remaining = count.take().unwrap_or(1);
+                while remaining > 0 {
+                    match advance(&mut universe, &mut watch, &mut graph, &mut stats) {
+                        Some(generations) => remaining = remaining.saturating_sub(generations.max(1)),
+                        None => break,
+                    }
+                }
+            }
+
+            // Change the speed
+            if input.key_pressed(VirtualKeyCode::Equals) || input.key_pressed(VirtualKeyCode::NumpadAdd) {
+                clock.faster();
+            }
+            if input.key_pressed(VirtualKeyCode::Minus) || input.key_pressed(VirtualKeyCode::NumpadSubtract) {
+                clock.slower();
+            }
+
+            // Update internal state and request a redraw
+            clock.tick(|| advance(&mut universe, &mut watch, &mut graph, &mut stats));
+            *control_flow = if clock.paused { ControlFlow::Wait } else { ControlFlow::Poll };
+            window.request_redraw();
+        }
+    })
+}
+
+/// Steps the universe once, records it in the graph and the stats file if there are any, and
+/// lets `watch` look at it. Returns the number of generations advanced, or `None` if the watch
+/// stopped the run or the hashlife plane overflowed.
+fn advance(universe: &mut Universe, watch: &mut Watch, graph: &mut Option<History>, stats: &mut Option<StatsFile>) -> Option<usize> {
+    let generation = universe.generation();
+    if let Err(error) = universe.step() {
+        eprintln!("error: {}", error);
+        return None;
+    }
+    let generations = universe.generation() - generation;
+    if let Some(history) = graph {
+        history.record(universe);
+    }
+    if let Some(stats) = stats {
+        stats.record(universe);
+    }
+    watch.check(universe).then_some(generations)
+}
+
+/// Draws the population and activity of the latest generations over the bottom of the frame,
+/// newest on the right, one pixel column per generation and scaled to the largest value shown.
+fn draw_graph(frame: &mut [u8], width: usize, history: &History) {
+    let height = frame.len() / 4 / width.max(1);
+    let graph_height = GRAPH_HEIGHT.min(height);
+    if graph_height < 2 {
+        return;
+    }
+    let top = height - graph_height;
+    for pixel in frame[top * width * 4..].chunks_exact_mut(4) {
+        for channel in &mut pixel[..3] {
+            *channel /= 4;
+        }
+    }
+
+    let samples: Vec<_> = history.samples().rev().take(width).collect();
+    let max = samples
+        .iter()
+        .map(|stats| stats.population.max(stats.activity().unwrap_or(0)))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let row = |value: usize| top + (graph_height - 1) - value * (graph_height - 1) / max;
+    let lines = [
+        (POPULATION_COLOR, samples.iter().map(|stats| Some(stats.population)).collect::<Vec<_>>()),
+        (ACTIVITY_COLOR, samples.iter().map(|stats| stats.activity()).collect()),
+    ];
+    for (color, values) in lines {
+        // join each point to the next older one with a vertical run so the line stays unbroken
+        for (i, value) in values.iter().enumerate() {
+            let Some(value) = value else {
+                continue;
+            };
+            let x = width - 1 - i;
+            let y = row(*value);
+            let next = values.get(i + 1).copied().flatten().map_or(y, row);
+            for y in y.min(next)..=y.max(next) {
+                frame[(y * width + x) * 4..(y * width + x + 1) * 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// The part of the board inside the rectangle spanned by two selected corners, as `(x, y, width,
+/// height)`, or `None` if it lies outside the board.
+fn board_region((from, to): ((i64, i64), (i64, i64)), universe: &Universe) -> Option<(usize, usize, usize, usize)> {
+    let span = |a: i64, b: i64, size: usize| {
+        let start = a.min(b).max(0);
+        let end = a.max(b).saturating_add(1).min(size as i64);
+        (start < end).then(|| (start as usize, (end - start) as usize))
+    };
+    let (x, width) = span(from.0, to.0, universe.width())?;
+    let (y, height) = span(from.1, to.1, universe.height())?;
+    Some((x, y, width, height))
+}
+
+/// Outlines a region of the board, given as `(x, y, width, height)`, in the frame.
+fn draw_selection(frame: &mut [u8], width: usize, camera: &Camera, (x, y, region_width, region_height): (usize, usize, usize, usize)) {
+    let height = frame.len() / 4 / width.max(1);
+    if width == 0 || height == 0 {
+        return;
+    }
+    let (left, top) = camera.pixel_at(x as i64, y as i64);
+    let (right, bottom) = camera.pixel_at((x + region_width) as i64, (y + region_height) as i64);
+    if right < 0.0 || bottom < 0.0 || left >= width as f64 || top >= height as f64 {
+        return;
+    }
+    // the outline runs along the pixels just inside the region, clipped to the frame
+    let clip = |value: f64, size: usize| value.clamp(0.0, (size - 1) as f64) as usize;
+    let (left, right) = (clip(left.floor(), width), clip(right.ceil() - 1.0, width));
+    let (top, bottom) = (clip(top.floor(), height), clip(bottom.ceil() - 1.0, height));
+    let mut plot = |px: usize, py: usize| frame[(py * width + px) * 4..(py * width + px + 1) * 4].copy_from_slice(&SELECTION_COLOR);
+    for px in left..=right {
+        plot(px, top);
+        plot(px, bottom);
+    }
+    for py in top..=bottom {
+        plot(left, py);
+        plot(right, py);
+    }
+}
+
+/// Sets or erases the cell at a board position that may lie beyond the board.
+fn paint(universe: &mut Universe, x: i64, y: i64, alive: bool) {
+    match (usize::try_from(x), usize::try_from(y)) {
+        (Ok(x), Ok(y)) if !alive && x < universe.width() && y < universe.height() => universe.erase(x, y),
+        _ => universe.set_alive_at(x, y, alive),
+    }
+}
+
+/// The cells on a straight line from `from` to `to`, both included.
+fn line(from: (i64, i64), to: (i64, i64)) -> impl Iterator<Item = (i64, i64)> {
+    let (dx, dy) = ((to.0 - from.0) as f64, (to.1 - from.1) as f64);
+    let steps = dx.abs().max(dy.abs()) as i64;
+    (0..=steps).map(move |i| {
+        let t = if steps == 0 { 0.0 } else { i as f64 / steps as f64 };
+        ((from.0 as f64 + dx * t).round() as i64, (from.1 as f64 + dy * t).round() as i64)
+    })
+}
+
+/// Paces the simulation at a target number of generations per second, independently of how
+/// often the window redraws.
+struct Clock {
+    /// Target generations per second, or `None` to step as fast as possible.
+    speed: Option<f64>,
+    paused: bool,
+    /// Generations that are due but not stepped yet.
+    due: f64,
+    last_tick: Instant,
+}
+
+impl Clock {
+    fn new(speed: Option<f64>, paused: bool) -> Self {
+        Clock {
+            speed,
+            paused,
+            due: 0.0,
+            last_tick: Instant::now(),
+        }
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.due = 0.0;
+    }
+
+    fn faster(&mut self) {
+        self.speed = self.speed.map(|speed| speed * 2.0).filter(|&speed| speed <= MAX_SPEED);
+        self.due = 0.0;
+        self.report_speed();
+    }
+
+    fn slower(&mut self) {
+        self.speed = Some(self.speed.map_or(DEFAULT_SPEED, |speed| (speed / 2.0).max(MIN_SPEED)));
+        self.due = 0.0;
+        self.report_speed();
+    }
+
+    fn report_speed(&self) {
+        match self.speed {
+            Some(speed) => println!("Speed: {} generations/s", speed),
+            None => println!("Speed: unlimited"),
+        }
+    }
+
+    /// Calls `step`, which returns the number of generations it advanced, as often as the time
+    /// since the last tick allows. Pauses if `step` returns `None`.
+    fn tick(&mut self, mut step: impl FnMut() -> Option<usize>) {
+        let elapsed = self.last_tick.elapsed();
+        self.last_tick = Instant::now();
+        if self.paused {
+            return;
+        }
+        match self.speed {
+            Some(speed) => {
+                self.due = (self.due + elapsed.as_secs_f64() * speed).min((speed * MAX_LAG).max(1.0));
+                while self.due >= 1.0 {
+                    match step() {
+                        // a hashlife jump can cover far more generations than are due; it must
+                        // not hold back the following steps
+                        Some(generations) => self.due = (self.due - generations.max(1) as f64).max(0.0),
+                        None => return self.set_paused(true),
+                    }
+                }
+            }
+            None => {
+                let start = Instant::now();
+                while start.elapsed() < FRAME_BUDGET {
+                    if step().is_none() {
+                        return self.set_paused(true);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use crate::grid::Grid;
+use crate::parallel::step_in_bands;
+use crate::rule::Rule;
+use crate::stats::Changes;
+use crate::topology::Topology;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A board storing one bit per cell, packed row by row into `u64` words.
+///
+/// Bit `j` of word `i` in a row is the cell at `x = i * 64 + j`. Two buffers are allocated up
+/// front and swapped after every generation, so stepping never copies the board. Neighbor counts
+/// are computed for 64 cells at once with bit-sliced adders.
+#[derive(Clone, Debug)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    current: Vec<u64>,
+    next: Vec<u64>,
+}
+
+impl BitGrid {
+    /// Creates a grid of `width` x `height` dead cells. Panics if either dimension is zero.
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0, "grid dimensions must be non-zero");
+        let words_per_row = width.div_ceil(BITS);
+        BitGrid {
+            width,
+            height,
+            words_per_row,
+            current: vec![0; words_per_row * height],
+            next: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn from_grid(grid: &Grid) -> Self {
+        let mut bits = BitGrid::new(grid.width(), grid.height());
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if grid.is_alive(x, y) {
+                    bits.set_alive(x, y, true);
+                }
+            }
+        }
+        bits
+    }
+
+    /// Converts back to a dense grid. Alive cells get full intensity, dead cells none.
+    pub fn to_grid(&self) -> Grid {
+        let mut grid = Grid::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.is_alive(x, y) {
+                    grid.set(x, y, (1, 0xff));
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.current[y * self.words_per_row + x / BITS] >> (x % BITS) & 1 == 1
+    }
+
+    pub fn set_alive(&mut self, x: usize, y: usize, alive: bool) {
+        let word = &mut self.current[y * self.words_per_row + x / BITS];
+        if alive {
+            *word |= 1 << (x % BITS);
+        } else {
+            *word &= !(1 << (x % BITS));
+        }
+    }
+
+    /// The packed words of the current generation, row by row.
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.current
+    }
+
+    pub fn population(&self) -> usize {
+        self.current.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Smallest rectangle holding all alive cells, as `(min_x, min_y, max_x, max_y)`, or `None`
+    /// if every cell is dead.
+    pub fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        let n = self.words_per_row;
+        let rows: Vec<usize> = (0..self.height).filter(|&y| self.current[y * n..(y + 1) * n].iter().any(|&word| word != 0)).collect();
+        let (&min_y, &max_y) = (rows.first()?, rows.last()?);
+        let (mut min_x, mut max_x) = (usize::MAX, 0);
+        for &y in &rows {
+            for (i, &word) in self.current[y * n..(y + 1) * n].iter().enumerate() {
+                if word != 0 {
+                    min_x = min_x.min(i * BITS + word.trailing_zeros() as usize);
+                    max_x = max_x.max(i * BITS + BITS - 1 - word.leading_zeros() as usize);
+                }
+            }
+        }
+        Some((min_x, min_y, max_x, max_y))
+    }
+
+    /// Advances the grid by one generation on the given topology, splitting the rows into bands
+    /// across `threads` worker threads. Returns the cells that were born and died.
+    pub fn step(&mut self, rule: &Rule, topology: Topology, threads: usize) -> Changes {
+        let mut next = std::mem::take(&mut self.next);
+        let changes = step_in_bands(&mut next, self.words_per_row, self.height, threads, |rows, out| {
+            self.step_rows(rule, topology, rows, out)
+        });
+        self.next = std::mem::replace(&mut self.current, next);
+        changes
+    }
+
+    /// Computes the next state of `rows` into `out`, which holds exactly those rows.
+    fn step_rows(&self, rule: &Rule, topology: Topology, rows: std::ops::Range<usize>, out: &mut [u64]) -> Changes {
+        let n = self.words_per_row;
+        let transitions: Vec<(u8, bool, bool)> = (0..=8)
+            .filter(|&count| rule.birth(count) || rule.survival(count))
+            .map(|count| (count, rule.birth(count), rule.survival(count)))
+            .collect();
+        let last_mask = match self.width % BITS {
+            0 => u64::MAX,
+            bits => (1 << bits) - 1,
+        };
+
+        // rolling extended copies of the rows above, at and below the current one
+        let mut above = vec![0; n + 1];
+        let mut mid = vec![0; n + 1];
+        let mut below = vec![0; n + 1];
+        let mut above_left = self.extend_row(topology, rows.start as isize - 1, &mut above);
+        let mut mid_left = self.extend_row(topology, rows.start as isize, &mut mid);
+        let mut changes = Changes::default();
+
+        for (y, out_row) in rows.zip(out.chunks_exact_mut(n)) {
+            let below_left = self.extend_row(topology, y as isize + 1, &mut below);
+            for (i, out_word) in out_row.iter_mut().enumerate() {
+                let west = |row: &[u64], left: bool| row[i] << 1 | if i == 0 { left as u64 } else { row[i - 1] >> 63 };
+                let east = |row: &[u64]| row[i] >> 1 | row[i + 1] << 63;
+                let neighbors = [
+                    west(&above, above_left),
+                    above[i],
+                    east(&above),
+                    west(&mid, mid_left),
+                    east(&mid),
+                    west(&below, below_left),
+                    below[i],
+                    east(&below),
+                ];
+
+                // bit-sliced counter: bit j of plane k is bit k of cell j's neighbor count
+                let mut planes = [0u64; 4];
+                for neighbor in neighbors {
+                    let mut carry = neighbor;
+                    for plane in planes.iter_mut() {
+                        let overflow = *plane & carry;
+                        *plane ^= carry;
+                        carry = overflow;
+                    }
+                }
+
+                let mask = if i == n - 1 { last_mask } else { u64::MAX };
+                let alive = mid[i] & mask;
+                let mut word = 0;
+                for &(count, birth, survival) in &transitions {
+                    let mut matches = u64::MAX;
+                    for (k, plane) in planes.iter().enumerate() {
+                        matches &= if count >> k & 1 == 1 { *plane } else { !*plane };
+                    }
+                    word |= matches & (if birth { !alive } else { 0 } | if survival { alive } else { 0 });
+                }
+                word &= mask;
+                changes.births += (word & !alive).count_ones() as usize;
+                changes.deaths += (alive & !word).count_ones() as usize;
+                *out_word = word;
+            }
+
+            std::mem::swap(&mut above, &mut mid);
+            std::mem::swap(&mut mid, &mut below);
+            above_left = mid_left;
+            mid_left = below_left;
+        }
+
+        changes
+    }
+
+    /// Copies row `y`, which may lie beyond the top or bottom edge, into `out` with the cell east
+    /// of the last column placed at bit `width`. Returns the cell west of the first column.
+    fn extend_row(&self, topology: Topology, y: isize, out: &mut [u64]) -> bool {
+        let n = self.words_per_row;
+        let cell = |x: isize| {
+            topology
+                .resolve(x, y, self.width, self.height)
+                .is_some_and(|(x, y)| self.is_alive(x, y))
+        };
+        if (0..self.height as isize).contains(&y) {
+            let y = y as usize;
+            out[..n].copy_from_slice(&self.current[y * n..(y + 1) * n]);
+        } else {
+            // rows beyond the edge may be flipped or dead, so they are assembled cell by cell
+            out[..n].fill(0);
+            for x in 0..self.width {
+                if cell(x as isize) {
+                    out[x / BITS] |= 1 << (x % BITS);
+                }
+            }
+        }
+        out[n] = 0;
+        if cell(self.width as isize) {
+            out[self.width / BITS] |= 1 << (self.width % BITS);
+        }
+        cell(-1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rule::Rule;
+    use crate::topology::Topology;
+    use crate::universe::{Engine, Universe};
+
+    const RULES: [&str; 7] = ["B3/S23", "B36/S23", "B2/S", "B3678/S34678", "B36/S125", "B3/S012345678", "B0/S8"];
+    const TOPOLOGIES: [Topology; 5] = [Topology::Torus, Topology::Dead, Topology::Reflect, Topology::Klein, Topology::Projective];
+    const SIZES: [(usize, usize); 7] = [(1, 1), (2, 3), (7, 1), (63, 5), (64, 9), (65, 17), (130, 12)];
+
+    fn alive(universe: &Universe) -> Vec<bool> {
+        (0..universe.height()).flat_map(|y| (0..universe.width()).map(move |x| universe.is_alive(x, y))).collect()
+    }
+
+    #[test]
+    fn packed_and_threaded_steps_match_single_threaded_dense() {
+        for (seed, rule) in RULES.iter().enumerate() {
+            let rule: Rule = rule.parse().unwrap();
+            for topology in TOPOLOGIES {
+                for (width, height) in SIZES {
+                    let universe = |engine, threads| {
+                        let mut universe = Universe::new(width, height).with_rule(rule).with_topology(topology).with_threads(threads);
+                        universe.seed_with(seed as u64, 0.4);
+                        universe.with_engine(engine)
+                    };
+                    let mut expected = universe(Engine::Dense, 1);
+                    let mut others: Vec<_> = [(Engine::Dense, 3), (Engine::Dense, 8), (Engine::Packed, 1), (Engine::Packed, 3), (Engine::Packed, 8)]
+                        .into_iter()
+                        .map(|(engine, threads)| (engine, threads, universe(engine, threads)))
+                        .collect();
+                    for generation in 1..=24 {
+                        expected.step().unwrap();
+                        for (engine, threads, other) in &mut others {
+                            other.step().unwrap();
+                            let case = format!("{} {:?} {}x{} {} on {} threads, generation {}", rule, topology, width, height, engine, threads, generation);
+                            assert_eq!(alive(other), alive(&expected), "{}", case);
+                            assert_eq!(other.stats().changes, expected.stats().changes, "{}", case);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
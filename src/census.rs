@@ -0,0 +1,522 @@
+//! Separating the ash of a settled soup into objects and identifying them, as apgsearch does.
+//!
+//! Objects are named by their apgcode: `xs<population>_` for still lifes, `xp<period>_` for
+//! oscillators and `xq<period>_` for spaceships, followed by the Extended Wechsler Format of the
+//! phase and orientation with the shortest, then alphabetically first, encoding. Objects that do
+//! not repeat within the period limit get `zz_` followed by the encoding of the phase they were
+//! found in.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::OnceLock;
+
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::sparse::SparseUniverse;
+use crate::topology::Topology;
+use crate::universe::Universe;
+
+/// Digits of the Extended Wechsler Format, one per 5-cell column of a strip.
+const DIGITS: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+
+/// Common B3/S23 objects, drawn with `o` for alive cells.
+const KNOWN_OBJECTS: &[(&str, &[&str])] = &[
+    ("block", &["oo", "oo"]),
+    ("beehive", &[".oo.", "o..o", ".oo."]),
+    ("loaf", &[".oo.", "o..o", ".o.o", "..o."]),
+    ("boat", &["oo.", "o.o", ".o."]),
+    ("ship", &["oo.", "o.o", ".oo"]),
+    ("tub", &[".o.", "o.o", ".o."]),
+    ("pond", &[".oo.", "o..o", "o..o", ".oo."]),
+    ("long boat", &["oo..", "o.o.", ".o.o", "..o."]),
+    ("barge", &[".o..", "o.o.", ".o.o", "..o."]),
+    ("snake", &["oo.o", "o.oo"]),
+    ("aircraft carrier", &["oo..", "o..o", "..oo"]),
+    ("eater 1", &["oo..", "o.o.", "..o.", "..oo"]),
+    ("mango", &[".oo.", "o..o", ".o..o", "..oo"]),
+    ("blinker", &["ooo"]),
+    ("toad", &[".ooo", "ooo."]),
+    ("beacon", &["oo..", "oo..", "..oo", "..oo"]),
+    ("pentadecathlon", &["..o....o..", "oo.oooo.oo", "..o....o.."]),
+    (
+        "pulsar",
+        &[
+            "..ooo...ooo..",
+            ".............",
+            "o....o.o....o",
+            "o....o.o....o",
+            "o....o.o....o",
+            "..ooo...ooo..",
+            ".............",
+            "..ooo...ooo..",
+            "o....o.o....o",
+            "o....o.o....o",
+            "o....o.o....o",
+            ".............",
+            "..ooo...ooo..",
+        ],
+    ),
+    ("glider", &[".o.", "..o", "ooo"]),
+    ("lightweight spaceship", &[".o..o", "o....", "o...o", "oooo."]),
+    ("middleweight spaceship", &["...o..", ".o...o", "o.....", "o....o", "ooooo."]),
+    ("heavyweight spaceship", &["...oo..", ".o....o", "o......", "o.....o", "oooooo."]),
+];
+
+/// How an object behaves when left on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator { period: usize },
+    /// Returns to its shape every `period` generations, moved by `displacement` cells.
+    Spaceship { period: usize, displacement: (i64, i64) },
+    /// Did not repeat within the period limit: it dies, grows or has a longer period.
+    Unknown,
+}
+
+/// A piece of ash identified by its apgcode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Object {
+    code: String,
+    kind: ObjectKind,
+    cells: Vec<(i64, i64)>,
+}
+
+impl Object {
+    /// Identifies the object formed by `cells` under `rule` by letting it evolve on its own for up
+    /// to `max_period` generations.
+    pub fn identify(cells: &[(i64, i64)], rule: Rule, max_period: usize) -> Self {
+        let (start, origin) = normalize(cells.to_vec());
+        let mut phases = vec![start.clone()];
+        let mut kind = ObjectKind::Unknown;
+        if !rule.birth(0) && !start.is_empty() {
+            let mut life = SparseUniverse::new(rule);
+            for &(x, y) in &start {
+                life.set_alive(x, y, true);
+            }
+            for generation in 1..=max_period {
+                life.step();
+                // whatever keeps growing will not repeat
+                if life.population() == 0 || life.population() > 4 * start.len() + 64 {
+                    break;
+                }
+                let (phase, corner) = normalize(life.cells().collect());
+                if phase == start {
+                    kind = match corner {
+                        (0, 0) if generation == 1 => ObjectKind::StillLife,
+                        (0, 0) => ObjectKind::Oscillator { period: generation },
+                        displacement => ObjectKind::Spaceship { period: generation, displacement },
+                    };
+                    break;
+                }
+                phases.push(phase);
+            }
+        }
+
+        let code = match kind {
+            ObjectKind::StillLife => format!("xs{}_{}", start.len(), canonical(&phases[..1])),
+            ObjectKind::Oscillator { period } => format!("xp{}_{}", period, canonical(&phases)),
+            ObjectKind::Spaceship { period, .. } => format!("xq{}_{}", period, canonical(&phases)),
+            ObjectKind::Unknown => format!("zz_{}", canonical(&phases[..1])),
+        };
+        let cells = start.into_iter().map(|(x, y)| (x + origin.0, y + origin.1)).collect();
+        Object { code, kind, cells }
+    }
+
+    /// The apgcode, e.g. `xs4_33` for a block.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn kind(&self) -> ObjectKind {
+        self.kind
+    }
+
+    /// The common name of the object if it is a well known B3/S23 object. Objects of other rules
+    /// may share codes with them, so only ask for names under B3/S23.
+    pub fn name(&self) -> Option<&'static str> {
+        known_objects().get(&self.code).copied()
+    }
+
+    /// The cells the object was found with, in board coordinates.
+    pub fn cells(&self) -> &[(i64, i64)] {
+        &self.cells
+    }
+
+    /// The object as found, as a pattern named by its code.
+    pub fn to_pattern(&self) -> Pattern {
+        let (cells, _) = normalize(self.cells.clone());
+        Pattern::from_cells(cells.into_iter().map(|(x, y)| (x as usize, y as usize))).with_name(self.code.clone())
+    }
+}
+
+/// Maps the apgcodes of [`KNOWN_OBJECTS`] to their names.
+fn known_objects() -> &'static HashMap<String, &'static str> {
+    static KNOWN: OnceLock<HashMap<String, &'static str>> = OnceLock::new();
+    KNOWN.get_or_init(|| {
+        KNOWN_OBJECTS
+            .iter()
+            .map(|&(name, rows)| {
+                let cells: Vec<(i64, i64)> = rows
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(y, row)| row.char_indices().filter(|&(_, c)| c == 'o').map(move |(x, _)| (x as i64, y as i64)))
+                    .collect();
+                (Object::identify(&cells, Rule::CONWAY, 64).code, name)
+            })
+            .collect()
+    })
+}
+
+/// Splits the alive cells of the board into objects: groups of cells linked by gaps of at most one
+/// dead cell, so any two cells that could influence each other's neighbors end up together.
+///
+/// On a torus, objects straddling the edges are reassembled. The other bounded topologies are
+/// treated as having dead edges, so objects straddling their edges are split. On the unbounded
+/// engines the whole plane is looked at.
+pub fn separate(universe: &Universe) -> Vec<Vec<(i64, i64)>> {
+    let mut cells: Vec<(i64, i64)> = match universe.plane_cells() {
+        Some(cells) => {
+            let (vx, vy) = universe.viewport();
+            cells.into_iter().map(|(x, y)| (x - vx, y - vy)).collect()
+        }
+        None => {
+            let (width, height) = (universe.width(), universe.height());
+            (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .filter(|&(x, y)| universe.is_alive(x, y))
+                .map(|(x, y)| (x as i64, y as i64))
+                .collect()
+        }
+    };
+    cells.sort_unstable();
+
+    let torus = universe.plane_cells().is_none() && universe.topology() == Topology::Torus;
+    let (width, height) = (universe.width() as i64, universe.height() as i64);
+    let wrap = |x: i64, y: i64| if torus { (x.rem_euclid(width), y.rem_euclid(height)) } else { (x, y) };
+
+    let mut unvisited: HashSet<(i64, i64)> = cells.iter().copied().collect();
+    let mut objects = Vec::new();
+    for start in cells {
+        if !unvisited.remove(&start) {
+            continue;
+        }
+        // every cell is queued with where it lies on the board and where it lies relative to the
+        // start without wrapping around
+        let mut queue = VecDeque::from([(start, start)]);
+        let mut object = Vec::new();
+        while let Some((cell, unwrapped)) = queue.pop_front() {
+            object.push(unwrapped);
+            for dy in -2..=2 {
+                for dx in -2..=2 {
+                    let neighbor = wrap(cell.0 + dx, cell.1 + dy);
+                    if unvisited.remove(&neighbor) {
+                        queue.push_back((neighbor, (unwrapped.0 + dx, unwrapped.1 + dy)));
+                    }
+                }
+            }
+        }
+        objects.push(object);
+    }
+    objects
+}
+
+/// Separates the board into objects and identifies each of them.
+pub fn objects(universe: &Universe, max_period: usize) -> Vec<Object> {
+    separate(universe)
+        .iter()
+        .flat_map(|cells| identify_group(cells, universe.rule(), max_period))
+        .collect()
+}
+
+/// Identifies a group of cells found by [`separate`], as the objects touching within it if they
+/// evolve independently of each other, like a blinker next to a beehive, or else as one object.
+fn identify_group(cells: &[(i64, i64)], rule: Rule, max_period: usize) -> Vec<Object> {
+    let parts = touching(cells);
+    if parts.len() > 1 {
+        let objects: Vec<Object> = parts.iter().map(|part| Object::identify(part, rule, max_period)).collect();
+        let period = objects.iter().try_fold(1, |period, object| match object.kind {
+            ObjectKind::StillLife => Some(period),
+            ObjectKind::Oscillator { period: other } | ObjectKind::Spaceship { period: other, .. } => Some(period / gcd(period, other) * other),
+            ObjectKind::Unknown => None,
+        });
+        if period.is_some_and(|period| period <= max_period && independent(cells, &parts, rule, period.max(2))) {
+            return objects;
+        }
+    }
+    vec![Object::identify(cells, rule, max_period)]
+}
+
+/// Splits cells into groups of cells touching each other, diagonals included.
+fn touching(cells: &[(i64, i64)]) -> Vec<Vec<(i64, i64)>> {
+    let mut unvisited: HashSet<(i64, i64)> = cells.iter().copied().collect();
+    let mut parts = Vec::new();
+    for &start in cells {
+        if !unvisited.remove(&start) {
+            continue;
+        }
+        let mut queue = VecDeque::from([start]);
+        let mut part = Vec::new();
+        while let Some((x, y)) = queue.pop_front() {
+            part.push((x, y));
+            for neighbor in (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (x + dx, y + dy))) {
+                if unvisited.remove(&neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        parts.push(part);
+    }
+    parts
+}
+
+/// Whether `cells` evolve for `generations` generations exactly as its `parts` do on their own.
+fn independent(cells: &[(i64, i64)], parts: &[Vec<(i64, i64)>], rule: Rule, generations: usize) -> bool {
+    let life = |cells: &[(i64, i64)]| {
+        let mut life = SparseUniverse::new(rule);
+        for &(x, y) in cells {
+            life.set_alive(x, y, true);
+        }
+        life
+    };
+    let mut whole = life(cells);
+    let mut parts: Vec<SparseUniverse> = parts.iter().map(|part| life(part)).collect();
+    for _ in 0..generations {
+        whole.step();
+        parts.iter_mut().for_each(|part| {
+            part.step();
+        });
+        let together: HashSet<(i64, i64)> = parts.iter().flat_map(SparseUniverse::cells).collect();
+        if whole.population() != together.len() || whole.cells().any(|cell| !together.contains(&cell)) {
+            return false;
+        }
+    }
+    true
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Running tally of the objects found in many settled soups.
+#[derive(Clone, Debug)]
+pub struct Census {
+    max_period: usize,
+    /// The first object found with each code, the seed of its soup and how often it was seen.
+    entries: HashMap<String, (Object, Option<u64>, usize)>,
+}
+
+impl Census {
+    /// Creates an empty census identifying objects with periods up to `max_period`.
+    pub fn new(max_period: usize) -> Self {
+        Census {
+            max_period,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Adds the objects on the board to the tally and returns them.
+    pub fn add(&mut self, universe: &Universe) -> Vec<Object> {
+        let objects = objects(universe, self.max_period);
+        for object in &objects {
+            self.entries
+                .entry(object.code.clone())
+                .or_insert_with(|| (object.clone(), universe.rng_seed(), 0))
+                .2 += 1;
+        }
+        objects
+    }
+
+    /// Every distinct object with how often it was seen, most common first.
+    pub fn tally(&self) -> Vec<(&Object, usize)> {
+        let mut tally: Vec<_> = self.entries.values().map(|(object, _, count)| (object, *count)).collect();
+        tally.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.code.cmp(&b.0.code)));
+        tally
+    }
+
+    /// The first sample of the object with the given code and the seed of the soup it came from.
+    pub fn sample(&self, code: &str) -> Option<(&Object, Option<u64>)> {
+        self.entries.get(code).map(|(object, seed, _)| (object, *seed))
+    }
+
+    /// Number of objects counted.
+    pub fn total(&self) -> usize {
+        self.entries.values().map(|&(_, _, count)| count).sum()
+    }
+}
+
+/// Sorts the cells and shifts them so their bounding box starts at `(0, 0)`. Also returns the
+/// original corner of the bounding box.
+fn normalize(mut cells: Vec<(i64, i64)>) -> (Vec<(i64, i64)>, (i64, i64)) {
+    let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    for cell in &mut cells {
+        *cell = (cell.0 - min_x, cell.1 - min_y);
+    }
+    cells.sort_unstable();
+    (cells, (min_x, min_y))
+}
+
+/// Maps a cell to its position in a rotated or reflected copy of an object.
+type Orientation = fn((i64, i64)) -> (i64, i64);
+
+/// The shortest, then alphabetically first, encoding of any phase in any of the 8 orientations.
+fn canonical(phases: &[Vec<(i64, i64)>]) -> String {
+    let orientations: [Orientation; 8] = [
+        |(x, y)| (x, y),
+        |(x, y)| (-x, y),
+        |(x, y)| (x, -y),
+        |(x, y)| (-x, -y),
+        |(x, y)| (y, x),
+        |(x, y)| (-y, x),
+        |(x, y)| (y, -x),
+        |(x, y)| (-y, -x),
+    ];
+    phases
+        .iter()
+        .flat_map(|phase| orientations.iter().map(move |orient| wechsler(&normalize(phase.iter().map(|&cell| orient(cell)).collect()).0)))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap_or_default()
+}
+
+/// Encodes normalized cells in the Extended Wechsler Format: strips of 5 rows separated by `z`,
+/// each column of a strip a base-32 digit with the top row as its lowest bit, and runs of empty
+/// columns shortened to `w` (2), `x` (3) or `y` plus a digit (4 to 39).
+fn wechsler(cells: &[(i64, i64)]) -> String {
+    let width = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0) as usize;
+    let height = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0) as usize;
+    let mut columns = vec![0u8; width * height.div_ceil(5)];
+    for &(x, y) in cells {
+        columns[(y as usize / 5) * width + x as usize] |= 1 << (y % 5);
+    }
+
+    let mut code = String::new();
+    for (strip, columns) in columns.chunks(width.max(1)).enumerate() {
+        if strip > 0 {
+            code.push('z');
+        }
+        let mut zeros = 0;
+        for &column in columns {
+            if column == 0 {
+                zeros += 1;
+                continue;
+            }
+            while zeros > 0 {
+                let run = zeros.min(39);
+                match run {
+                    1 => code.push('0'),
+                    2 => code.push('w'),
+                    3 => code.push('x'),
+                    _ => {
+                        code.push('y');
+                        code.push((b"0123456789abcdefghijklmnopqrstuvwxyz"[run - 4]) as char);
+                    }
+                }
+                zeros -= run;
+            }
+            code.push(DIGITS[column as usize] as char);
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{objects, separate, Census, Object, ObjectKind};
+    use crate::rule::Rule;
+    use crate::topology::Topology;
+    use crate::universe::{Engine, Universe};
+
+    /// The cells drawn with `o`, shifted by `(x, y)`.
+    fn cells(rows: &[&str], x: i64, y: i64) -> Vec<(i64, i64)> {
+        let alive = rows.iter().enumerate().flat_map(|(row, line)| line.char_indices().filter(|&(_, c)| c == 'o').map(move |(column, _)| (column, row)));
+        alive.map(|(column, row)| (x + column as i64, y + row as i64)).collect()
+    }
+
+    fn identify(rows: &[&str]) -> Object {
+        Object::identify(&cells(rows, 0, 0), Rule::CONWAY, 64)
+    }
+
+    #[test]
+    fn names_common_objects_by_apgcode() {
+        let expected = [
+            (&["oo", "oo"][..], "xs4_33", Some("block")),
+            (&["ooo"], "xp2_7", Some("blinker")),
+            (&[".o.", "..o", "ooo"], "xq4_153", Some("glider")),
+            (&[".oo.", "o..o", ".oo."], "xs6_696", Some("beehive")),
+            (&[".o..o", "o....", "o...o", "oooo."], "xq4_6frc", Some("lightweight spaceship")),
+            // the R-pentomino keeps growing for over a thousand generations
+            (&[".oo", "oo.", ".o."], "zz_", None),
+        ];
+        for (rows, code, name) in expected {
+            let object = identify(rows);
+            assert!(object.code().starts_with(code), "{:?} is {}, expected {}", rows, object.code(), code);
+            assert_eq!(object.name(), name, "{:?}", rows);
+        }
+    }
+
+    #[test]
+    fn codes_ignore_phase_and_orientation() {
+        let glider = identify(&[".o.", "..o", "ooo"]);
+        for rows in [&["o.o", ".oo", ".o."][..], &["ooo", "o..", ".o."], &[".o.", "o..", "ooo"], &["..o", "o.o", ".oo"]] {
+            assert_eq!(identify(rows).code(), glider.code(), "{:?}", rows);
+        }
+        assert_eq!(identify(&["o", "o", "o"]).code(), "xp2_7");
+    }
+
+    #[test]
+    fn classifies_behavior() {
+        assert_eq!(identify(&["oo", "oo"]).kind(), ObjectKind::StillLife);
+        assert_eq!(identify(&["ooo"]).kind(), ObjectKind::Oscillator { period: 2 });
+        assert_eq!(identify(&[".o.", "..o", "ooo"]).kind(), ObjectKind::Spaceship { period: 4, displacement: (1, 1) });
+        assert_eq!(identify(&[".o..o", "o....", "o...o", "oooo."]).kind(), ObjectKind::Spaceship { period: 4, displacement: (-2, 0) });
+    }
+
+    fn universe(width: usize, height: usize, topology: Topology, engine: Engine, objects: &[(&[&str], i64, i64)]) -> Universe {
+        let mut universe = Universe::new(width, height).with_topology(topology).with_engine(engine);
+        for &(rows, x, y) in objects {
+            for (x, y) in cells(rows, x, y) {
+                universe.set_alive_at(x, y, true);
+            }
+        }
+        universe
+    }
+
+    #[test]
+    fn separates_objects_across_torus_edges() {
+        // a block split over all four corners
+        let corners: &[(&[&str], i64, i64)] = &[(&["o"], 0, 0), (&["o"], 9, 0), (&["o"], 0, 9), (&["o"], 9, 9)];
+        let torus = universe(10, 10, Topology::Torus, Engine::Packed, corners);
+        let found = separate(&torus);
+        assert_eq!(found.len(), 1);
+        assert_eq!(objects(&torus, 64)[0].code(), "xs4_33");
+        assert_eq!(separate(&universe(10, 10, Topology::Dead, Engine::Packed, corners)).len(), 4);
+        // the plane of the unbounded engines has no edges to wrap around
+        assert_eq!(separate(&universe(10, 10, Topology::Torus, Engine::Sparse, corners)).len(), 4);
+    }
+
+    #[test]
+    fn tallies_ash() {
+        let ash: &[(&[&str], i64, i64)] = &[
+            // two blocks one dead column apart stay separate objects
+            (&["oo", "oo"], 2, 2),
+            (&["oo", "oo"], 5, 2),
+            (&["ooo"], 20, 20),
+            (&[".oo.", "o..o", ".oo."], 2, 20),
+        ];
+        let mut census = Census::new(64);
+        for engine in [Engine::Dense, Engine::HashLife] {
+            let found = census.add(&universe(32, 32, Topology::Torus, engine, ash));
+            assert_eq!(found.len(), 4, "{}", engine);
+        }
+        let tally: Vec<(&str, usize)> = census.tally().into_iter().map(|(object, count)| (object.code(), count)).collect();
+        assert_eq!(tally, [("xs4_33", 4), ("xp2_7", 2), ("xs6_696", 2)]);
+        assert_eq!(census.total(), 8);
+        let (block, _) = census.sample("xs4_33").unwrap();
+        let mut found = block.to_pattern().cells().to_vec();
+        found.sort_unstable();
+        assert_eq!(found, [(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::universe::Universe;
+
+/// Longest period a [`CycleDetector`] looks for unless told otherwise.
+pub const MAX_PERIOD: usize = 1024;
+
+/// How a universe that has stopped evolving repeats itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Cycle {
+    /// Every cell is dead.
+    Extinct,
+    /// The board no longer changes.
+    StillLife,
+    /// The board returns to the same state every `period` generations.
+    Oscillator { period: usize },
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cycle::Extinct => write!(f, "extinct"),
+            Cycle::StillLife => write!(f, "still life"),
+            Cycle::Oscillator { period } => write!(f, "oscillator with period {}", period),
+        }
+    }
+}
+
+/// Recognizes a universe returning to an earlier state by remembering a hash of each generation
+/// it is shown, up to `max_period` generations back.
+///
+/// States are compared by a 64-bit hash of their alive cells, so a collision could in principle
+/// report a cycle that is not there. The hashlife engine is only seen at the generations it stops
+/// at, so periods come out as multiples of `2^step_log` and oscillators whose period divides it
+/// look like still lifes.
+#[derive(Clone, Debug)]
+pub struct CycleDetector {
+    max_period: usize,
+    /// Latest generation each hash was seen at.
+    seen: HashMap<u64, usize>,
+    /// Observations oldest first, for forgetting the ones beyond `max_period`.
+    history: VecDeque<(u64, usize)>,
+    /// Whether the cycle the universe is in has been reported already.
+    settled: bool,
+}
+
+impl CycleDetector {
+    pub fn new(max_period: usize) -> Self {
+        CycleDetector {
+            max_period,
+            seen: HashMap::new(),
+            history: VecDeque::new(),
+            settled: false,
+        }
+    }
+
+    pub fn max_period(&self) -> usize {
+        self.max_period
+    }
+
+    /// Records the current state of `universe`, which should be shown after every step.
+    ///
+    /// Returns the cycle the first time the universe repeats a state. While it keeps cycling
+    /// nothing more is returned; once it leaves the cycle, for instance because cells were
+    /// edited, it is watched afresh.
+    pub fn observe(&mut self, universe: &Universe) -> Option<Cycle> {
+        let (hash, generation) = (universe.state_hash(), universe.generation());
+        while let Some(&(old_hash, old_generation)) = self.history.front() {
+            if generation.saturating_sub(old_generation) <= self.max_period {
+                break;
+            }
+            self.history.pop_front();
+            if self.seen.get(&old_hash) == Some(&old_generation) {
+                self.seen.remove(&old_hash);
+            }
+        }
+
+        let last_hash = self.history.back().map(|&(hash, _)| hash);
+        let previous = self.seen.insert(hash, generation);
+        self.history.push_back((hash, generation));
+        let period = match previous {
+            Some(previous) if previous < generation => generation - previous,
+            Some(_) => return None,
+            None => {
+                self.settled = false;
+                return None;
+            }
+        };
+        if self.settled {
+            return None;
+        }
+        self.settled = true;
+        Some(if universe.population() == 0 {
+            Cycle::Extinct
+        } else if last_hash == Some(hash) {
+            Cycle::StillLife
+        } else {
+            Cycle::Oscillator { period }
+        })
+    }
+
+    /// Forgets all observed states, as when the board has been replaced.
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.history.clear();
+        self.settled = false;
+    }
+}
+
+impl Default for CycleDetector {
+    fn default() -> Self {
+        CycleDetector::new(MAX_PERIOD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cycle, CycleDetector};
+    use crate::pattern::Pattern;
+    use crate::universe::{Engine, Universe};
+
+    /// Steps a universe holding `rle` at `(4, 4)` until the detector reports a cycle, giving up
+    /// after `generations`.
+    fn watch(rle: &str, engine: Engine, width: usize, height: usize, generations: usize) -> Option<(Cycle, usize)> {
+        let mut universe = Universe::new(width, height).with_engine(engine);
+        universe.place(&Pattern::from_rle(rle).unwrap(), 4, 4);
+        let mut detector = CycleDetector::default();
+        while universe.generation() <= generations {
+            if let Some(cycle) = detector.observe(&universe) {
+                return Some((cycle, universe.generation()));
+            }
+            universe.step().unwrap();
+        }
+        None
+    }
+
+    const BLOCK: &str = "x = 2, y = 2\n2o$2o!";
+    const BLINKER: &str = "x = 3, y = 1\n3o!";
+    const GLIDER: &str = "x = 3, y = 3\nbo$2bo$3o!";
+    const PENTADECATHLON: &str = "x = 10, y = 3\n2bo4bo$2ob4ob2o$2bo4bo!";
+
+    #[test]
+    fn reports_periods_on_every_engine() {
+        for engine in [Engine::Dense, Engine::Packed, Engine::HashLife, Engine::Sparse] {
+            assert_eq!(watch(BLOCK, engine, 32, 32, 10), Some((Cycle::StillLife, 1)), "{}", engine);
+            assert_eq!(watch(BLINKER, engine, 32, 32, 10), Some((Cycle::Oscillator { period: 2 }, 2)), "{}", engine);
+            assert_eq!(watch(PENTADECATHLON, engine, 32, 32, 40), Some((Cycle::Oscillator { period: 15 }, 15)), "{}", engine);
+            // a lone cell dies out
+            assert_eq!(watch("x = 1, y = 1\no!", engine, 32, 32, 10), Some((Cycle::Extinct, 2)), "{}", engine);
+        }
+    }
+
+    #[test]
+    fn spaceships_repeat_only_on_a_torus() {
+        // a glider moves one cell diagonally every 4 generations, so it is back after 4 * 16
+        assert_eq!(watch(GLIDER, Engine::Packed, 16, 16, 100), Some((Cycle::Oscillator { period: 64 }, 64)));
+        assert_eq!(watch(GLIDER, Engine::Sparse, 16, 16, 100), None);
+    }
+
+    #[test]
+    fn reports_a_cycle_once_until_it_is_left() {
+        let mut universe = Universe::new(16, 16);
+        universe.place(&Pattern::from_rle(BLINKER).unwrap(), 4, 4);
+        let mut detector = CycleDetector::new(8);
+        let mut reports = Vec::new();
+        for generation in 0..20 {
+            if generation == 10 {
+                // a block far enough away not to touch the blinker
+                universe.place(&Pattern::from_rle(BLOCK).unwrap(), 12, 12);
+            }
+            if let Some(cycle) = detector.observe(&universe) {
+                reports.push((cycle, universe.generation()));
+            }
+            universe.step().unwrap();
+        }
+        assert_eq!(reports, [(Cycle::Oscillator { period: 2 }, 2), (Cycle::Oscillator { period: 2 }, 12)]);
+    }
+
+    #[test]
+    fn forgets_states_beyond_the_longest_period() {
+        let mut universe = Universe::new(32, 32);
+        universe.place(&Pattern::from_rle(PENTADECATHLON).unwrap(), 4, 4);
+        let mut detector = CycleDetector::new(14);
+        for _ in 0..60 {
+            assert_eq!(detector.observe(&universe), None);
+            universe.step().unwrap();
+        }
+    }
+}
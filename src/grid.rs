@@ -0,0 +1,68 @@
+/// A heap-backed board whose dimensions are chosen at runtime.
+///
+/// Every cell is an `(alive, fade)` tuple: `alive` is 1 or 0, `fade` is the rendered intensity
+/// which decays once a cell dies. Cells are stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    cells: Vec<(u8, u8)>,
+}
+
+impl Grid {
+    /// Creates a grid of `width` x `height` dead cells. Panics if either dimension is zero.
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0, "grid dimensions must be non-zero");
+        Grid {
+            width,
+            height,
+            cells: vec![(0, 0); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> (u8, u8) {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: (u8, u8)) {
+        self.cells[y * self.width + x] = cell;
+    }
+
+    pub fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.get(x, y).0 == 1
+    }
+
+    pub fn fade(&self, x: usize, y: usize) -> u8 {
+        self.get(x, y).1
+    }
+
+    pub fn set_alive(&mut self, x: usize, y: usize, alive: bool) {
+        let fade = self.fade(x, y);
+        self.set(x, y, if alive { (1, 0xff) } else { (0, fade) });
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.0 == 1).count()
+    }
+
+    pub fn cells(&self) -> &[(u8, u8)] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [(u8, u8)] {
+        &mut self.cells
+    }
+
+    /// Iterates over the rows of the grid, top to bottom.
+    pub fn rows(&self) -> std::slice::ChunksExact<'_, (u8, u8)> {
+        self.cells.chunks_exact(self.width)
+    }
+}
@@ -0,0 +1,568 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::grid::Grid;
+use crate::rule::Rule;
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// Number of interned nodes after which unreachable nodes and memoized results are dropped.
+const GARBAGE_THRESHOLD: usize = 1 << 22;
+
+/// Deepest root, whose side of `2^MAX_LEVEL` cells still fits an `i64` twice over.
+const MAX_LEVEL: u8 = 61;
+
+/// Largest supported step. A step needs a root of at least `step_log + 3` levels, and the root
+/// and its coordinates have to fit in an `i64` with room left for the pattern to grow.
+pub const MAX_STEP_LOG: u8 = 56;
+
+/// Modulus of the hash of a set of cells, the Mersenne prime `2^61 - 1`.
+const HASH_MODULUS: u64 = (1 << 61) - 1;
+/// A set of cells hashes to the sum of `HASH_X^x * HASH_Y^y` over its cells, modulo
+/// [`HASH_MODULUS`]. Unlike hashing the cell list, this is the same however the quadtree is laid
+/// out and can be built up from the children of each node.
+const HASH_X: u64 = 0x0e3d_7a5b_19c4_f261;
+const HASH_Y: u64 = 0x1b87_2c6d_f054_93a7;
+/// `HASH_X^(2^k)` and `HASH_Y^(2^k)`, the factors shifting a hash by the side of a level `k` node.
+const HASH_X_SHIFTS: [u64; 64] = hash_shifts(HASH_X);
+const HASH_Y_SHIFTS: [u64; 64] = hash_shifts(HASH_Y);
+
+/// A quadtree node of level `k`, covering `2^k` x `2^k` cells. Level 0 nodes are single cells.
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    level: u8,
+    /// nw, ne, sw, se
+    children: [NodeId; 4],
+    population: u64,
+    /// Hash of the alive cells relative to the node's top-left corner.
+    hash: u64,
+}
+
+/// The pattern has spread further than the `i64` coordinates of the plane can reach.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlaneOverflow;
+
+impl fmt::Display for PlaneOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the pattern outgrew the 64-bit coordinates of the hashlife plane")
+    }
+}
+
+impl Error for PlaneOverflow {}
+
+/// A Hashlife engine: an unbounded plane stored as a hash-consed quadtree, advanced by memoizing
+/// the future of every distinct square it encounters.
+///
+/// The universe can jump `2^step_log` generations per [`HashLife::step`], which makes patterns
+/// with a lot of regularity (guns, breeders, oscillators) cheap to run for billions of
+/// generations. Rules with `B0` make empty space flicker and are not supported.
+#[derive(Clone, Debug)]
+pub struct HashLife {
+    rule: Rule,
+    nodes: Vec<Node>,
+    interned: HashMap<[NodeId; 4], NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    root: NodeId,
+    /// Coordinates of the top-left cell of `root`.
+    origin: (i64, i64),
+    step_log: u8,
+    generation: u64,
+}
+
+impl HashLife {
+    /// Creates an empty plane. Panics if the rule births cells with zero neighbors.
+    pub fn new(rule: Rule) -> Self {
+        assert!(!rule.birth(0), "hashlife does not support B0 rules");
+        let mut life = HashLife {
+            rule,
+            nodes: vec![
+                Node { level: 0, children: [DEAD; 4], population: 0, hash: 0 },
+                Node { level: 0, children: [DEAD; 4], population: 1, hash: 1 },
+            ],
+            interned: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+            origin: (0, 0),
+            step_log: 0,
+            generation: 0,
+        };
+        life.root = life.empty(3);
+        life
+    }
+
+    /// Imports the alive cells of a dense grid, with the grid's top-left cell at `(0, 0)`.
+    pub fn from_grid(grid: &Grid, rule: Rule) -> Self {
+        let mut life = HashLife::new(rule);
+        let size = grid.width().max(grid.height());
+        let level = (usize::BITS - (size - 1).leading_zeros()).max(3) as u8;
+        life.root = life.build(grid, level, 0, 0);
+        life
+    }
+
+    fn build(&mut self, grid: &Grid, level: u8, x: usize, y: usize) -> NodeId {
+        if x >= grid.width() || y >= grid.height() {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return if grid.is_alive(x, y) { ALIVE } else { DEAD };
+        }
+        let half = 1 << (level - 1);
+        let nw = self.build(grid, level - 1, x, y);
+        let ne = self.build(grid, level - 1, x + half, y);
+        let sw = self.build(grid, level - 1, x, y + half);
+        let se = self.build(grid, level - 1, x + half, y + half);
+        self.node(nw, ne, sw, se)
+    }
+
+    /// Copies the cells covered by `grid`, with its top-left cell at `offset`, into it. Cells
+    /// outside the grid are not exported; fade values are reset.
+    pub fn export(&self, grid: &mut Grid, offset: (i64, i64)) {
+        self.export_scaled(grid, offset, 0);
+    }
+
+    /// Like [`HashLife::export`], but every cell of `grid` stands for a `2^shift` x `2^shift`
+    /// square of the plane and is alive if anything in that square is. Whole quadtree nodes of
+    /// that size are looked at instead of single cells, so this stays cheap when zoomed out.
+    pub fn export_scaled(&self, grid: &mut Grid, offset: (i64, i64), shift: u32) {
+        for cell in grid.cells_mut() {
+            *cell = (0, 0);
+        }
+        let (x, y) = (self.origin.0.saturating_sub(offset.0), self.origin.1.saturating_sub(offset.1));
+        self.export_node(self.root, x, y, shift, grid);
+    }
+
+    fn export_node(&self, id: NodeId, x: i64, y: i64, shift: u32, grid: &mut Grid) {
+        let node = self.nodes[id as usize];
+        let size = 1i64 << node.level;
+        let (width, height) = ((grid.width() as i64) << shift, (grid.height() as i64) << shift);
+        if node.population == 0 || x >= width || y >= height || x.saturating_add(size) <= 0 || y.saturating_add(size) <= 0 {
+            return;
+        }
+        // stop at the first node that lies within a single grid cell
+        let inside = |start: i64| start >> shift == (start + size - 1) >> shift;
+        if node.level as u32 <= shift && inside(x) && inside(y) {
+            grid.set((x >> shift) as usize, (y >> shift) as usize, (1, 0xff));
+            return;
+        }
+        let half = size / 2;
+        let [nw, ne, sw, se] = node.children;
+        self.export_node(nw, x, y, shift, grid);
+        self.export_node(ne, x + half, y, shift, grid);
+        self.export_node(sw, x, y + half, shift, grid);
+        self.export_node(se, x + half, y + half, shift, grid);
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Changes the rule, dropping all memoized results. Panics on `B0` rules.
+    pub fn set_rule(&mut self, rule: Rule) {
+        assert!(!rule.birth(0), "hashlife does not support B0 rules");
+        self.rule = rule;
+        self.results.clear();
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    pub fn step_log(&self) -> u8 {
+        self.step_log
+    }
+
+    /// Sets how far [`HashLife::step`] jumps: `2^step_log` generations, capped at `MAX_STEP_LOG`.
+    pub fn set_step_log(&mut self, step_log: u8) {
+        self.step_log = step_log.min(MAX_STEP_LOG);
+    }
+
+    /// A hash of the alive cells and where they are, for recognizing repeated states. Computed
+    /// from memoized per-node hashes, so it costs nothing like enumerating the cells.
+    pub fn state_hash(&self) -> u64 {
+        let offset = hash_mul(hash_pow(HASH_X, self.origin.0), hash_pow(HASH_Y, self.origin.1));
+        hash_mul(self.nodes[self.root as usize].hash, offset)
+    }
+
+    /// Coordinates of all alive cells on the plane.
+    pub fn cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::with_capacity(self.population() as usize);
+        self.collect_cells(self.root, self.origin.0, self.origin.1, &mut cells);
+        cells
+    }
+
+    fn collect_cells(&self, id: NodeId, x: i64, y: i64, cells: &mut Vec<(i64, i64)>) {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            cells.push((x, y));
+            return;
+        }
+        let half = 1i64 << (node.level - 1);
+        let [nw, ne, sw, se] = node.children;
+        self.collect_cells(nw, x, y, cells);
+        self.collect_cells(ne, x + half, y, cells);
+        self.collect_cells(sw, x, y + half, cells);
+        self.collect_cells(se, x + half, y + half, cells);
+    }
+
+    /// Smallest rectangle holding all alive cells, as `(min_x, min_y, max_x, max_y)`, or `None`
+    /// if the plane is empty. Each distinct node is only measured once.
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let (x, y) = self.origin;
+        let bounds = self.node_bounds(self.root, &mut HashMap::new())?;
+        Some((bounds.0 + x, bounds.1 + y, bounds.2 + x, bounds.3 + y))
+    }
+
+    /// Bounding box of the alive cells of a node relative to its top-left cell.
+    fn node_bounds(&self, id: NodeId, memo: &mut HashMap<NodeId, Option<(i64, i64, i64, i64)>>) -> Option<(i64, i64, i64, i64)> {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            return Some((0, 0, 0, 0));
+        }
+        if let Some(&bounds) = memo.get(&id) {
+            return bounds;
+        }
+        let half = 1i64 << (node.level - 1);
+        let offsets = [(0, 0), (half, 0), (0, half), (half, half)];
+        let bounds = node
+            .children
+            .into_iter()
+            .zip(offsets)
+            .filter_map(|(child, (dx, dy))| {
+                self.node_bounds(child, memo)
+                    .map(|(min_x, min_y, max_x, max_y)| (min_x + dx, min_y + dy, max_x + dx, max_y + dy))
+            })
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)));
+        memo.insert(id, bounds);
+        bounds
+    }
+
+    pub fn is_alive(&self, x: i64, y: i64) -> bool {
+        let mut id = self.root;
+        let (mut x, mut y) = (x - self.origin.0, y - self.origin.1);
+        let size = 1i64 << self.nodes[id as usize].level;
+        if x < 0 || y < 0 || x >= size || y >= size {
+            return false;
+        }
+        while self.nodes[id as usize].level > 0 {
+            let node = self.nodes[id as usize];
+            let half = 1i64 << (node.level - 1);
+            let quadrant = (x >= half) as usize + 2 * (y >= half) as usize;
+            id = node.children[quadrant];
+            x %= half;
+            y %= half;
+        }
+        id == ALIVE
+    }
+
+    pub fn set_alive(&mut self, x: i64, y: i64, alive: bool) {
+        loop {
+            let size = 1i64 << self.nodes[self.root as usize].level;
+            let (rx, ry) = (x - self.origin.0, y - self.origin.1);
+            if rx >= 0 && ry >= 0 && rx < size && ry < size {
+                self.root = self.set_node(self.root, rx, ry, alive);
+                return;
+            }
+            self.expand().expect("cell lies beyond the i64 coordinate range of the plane");
+        }
+    }
+
+    fn set_node(&mut self, id: NodeId, x: i64, y: i64, alive: bool) -> NodeId {
+        let node = self.nodes[id as usize];
+        if node.level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let half = 1i64 << (node.level - 1);
+        let quadrant = (x >= half) as usize + 2 * (y >= half) as usize;
+        let mut children = node.children;
+        children[quadrant] = self.set_node(children[quadrant], x % half, y % half, alive);
+        let [nw, ne, sw, se] = children;
+        self.node(nw, ne, sw, se)
+    }
+
+    /// Advances the plane by `2^step_log` generations. Returns `false` if nothing changed, and an
+    /// error without stepping if the pattern would spread beyond the coordinate range.
+    pub fn step(&mut self) -> Result<bool, PlaneOverflow> {
+        let step_log = self.step_log;
+        while self.nodes[self.root as usize].level < step_log + 2 || !self.is_padded(self.root) {
+            self.expand()?;
+        }
+        // one more level of empty border, so the pattern cannot grow out of the result
+        self.expand()?;
+
+        let level = self.nodes[self.root as usize].level;
+        let before = self.centre(self.root);
+        let after = self.advance(self.root, step_log);
+        let quarter = 1i64 << (level - 2);
+        self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+        self.root = after;
+        self.generation = self.generation.saturating_add(1 << step_log);
+
+        if self.nodes.len() > GARBAGE_THRESHOLD {
+            self.collect_garbage();
+        }
+        Ok(before != after)
+    }
+
+    fn node(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let children = [nw, ne, sw, se];
+        if let Some(&id) = self.interned.get(&children) {
+            return id;
+        }
+        let id = self.nodes.len() as NodeId;
+        let level = self.nodes[nw as usize].level;
+        let [nw_hash, ne_hash, sw_hash, se_hash] = children.map(|child| self.nodes[child as usize].hash);
+        let (right, down) = (HASH_X_SHIFTS[level as usize], HASH_Y_SHIFTS[level as usize]);
+        let hash = (nw_hash + hash_mul(ne_hash, right)) % HASH_MODULUS;
+        let hash = (hash + hash_mul(sw_hash + hash_mul(se_hash, right), down)) % HASH_MODULUS;
+        self.nodes.push(Node {
+            level: level + 1,
+            children,
+            population: children.iter().map(|&child| self.nodes[child as usize].population).sum(),
+            hash,
+        });
+        self.interned.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().unwrap();
+            let next = self.node(e, e, e, e);
+            self.empty.push(next);
+        }
+        self.empty[level as usize]
+    }
+
+    /// Whether all alive cells of a node lie within its centered half-size square.
+    fn is_padded(&self, id: NodeId) -> bool {
+        let node = self.nodes[id as usize];
+        if node.level < 2 {
+            return node.population == 0;
+        }
+        let inner: u64 = node
+            .children
+            .iter()
+            .enumerate()
+            .map(|(quadrant, &child)| self.nodes[self.nodes[child as usize].children[3 - quadrant] as usize].population)
+            .sum();
+        inner == node.population
+    }
+
+    /// Doubles the size of the root, keeping the current root centered. Leaves the plane
+    /// untouched if the larger root would not fit the coordinate range.
+    fn expand(&mut self) -> Result<(), PlaneOverflow> {
+        let root = self.nodes[self.root as usize];
+        if root.level >= MAX_LEVEL {
+            return Err(PlaneOverflow);
+        }
+        let half = 1i64 << (root.level - 1);
+        let origin = (self.origin.0.checked_sub(half).ok_or(PlaneOverflow)?, self.origin.1.checked_sub(half).ok_or(PlaneOverflow)?);
+        let e = self.empty(root.level - 1);
+        let [nw, ne, sw, se] = root.children;
+        let nw = self.node(e, e, e, nw);
+        let ne = self.node(e, e, ne, e);
+        let sw = self.node(e, sw, e, e);
+        let se = self.node(se, e, e, e);
+        self.root = self.node(nw, ne, sw, se);
+        self.origin = origin;
+        Ok(())
+    }
+
+    /// The centered node one level below `id`.
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.nodes[id as usize].children;
+        let child = |life: &Self, id: NodeId, quadrant: usize| life.nodes[id as usize].children[quadrant];
+        let (a, b, c, d) = (child(self, nw, 3), child(self, ne, 2), child(self, sw, 1), child(self, se, 0));
+        self.node(a, b, c, d)
+    }
+
+    /// The centered node one level below `id`, `2^step_log` generations later. Requires
+    /// `level >= 2` and `step_log <= level - 2`.
+    fn advance(&mut self, id: NodeId, step_log: u8) -> NodeId {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, step_log)) {
+            return result;
+        }
+
+        let result = if node.level == 2 {
+            self.advance_base(id)
+        } else {
+            // 4x4 grid of grandchildren, then the 9 overlapping squares one level below `id`
+            let mut grand = [[DEAD; 4]; 4];
+            for (quadrant, &child) in node.children.iter().enumerate() {
+                for (sub, &grandchild) in self.nodes[child as usize].children.iter().enumerate() {
+                    grand[quadrant / 2 * 2 + sub / 2][quadrant % 2 * 2 + sub % 2] = grandchild;
+                }
+            }
+            let full_speed = step_log == node.level - 2;
+            let mut parts = [[DEAD; 3]; 3];
+            for (i, row) in parts.iter_mut().enumerate() {
+                for (j, part) in row.iter_mut().enumerate() {
+                    let square = self.node(grand[i][j], grand[i][j + 1], grand[i + 1][j], grand[i + 1][j + 1]);
+                    *part = if full_speed { self.advance(square, node.level - 3) } else { self.centre(square) };
+                }
+            }
+            let next_log = if full_speed { node.level - 3 } else { step_log };
+            let mut quadrants = [DEAD; 4];
+            for (quadrant, result) in quadrants.iter_mut().enumerate() {
+                let (i, j) = (quadrant / 2, quadrant % 2);
+                let square = self.node(parts[i][j], parts[i][j + 1], parts[i + 1][j], parts[i + 1][j + 1]);
+                *result = self.advance(square, next_log);
+            }
+            let [nw, ne, sw, se] = quadrants;
+            self.node(nw, ne, sw, se)
+        };
+
+        self.results.insert((id, step_log), result);
+        result
+    }
+
+    /// Brute-forces one generation of the centered 2x2 cells of a 4x4 node.
+    fn advance_base(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[false; 4]; 4];
+        for (quadrant, &child) in self.nodes[id as usize].children.iter().enumerate() {
+            for (sub, &leaf) in self.nodes[child as usize].children.iter().enumerate() {
+                cells[quadrant / 2 * 2 + sub / 2][quadrant % 2 * 2 + sub % 2] = leaf == ALIVE;
+            }
+        }
+        let mut next = [DEAD; 4];
+        for (i, cell) in next.iter_mut().enumerate() {
+            let (y, x) = (1 + i / 2, 1 + i % 2);
+            let neighbors = cells[y - 1..=y + 1]
+                .iter()
+                .flat_map(|row| &row[x - 1..=x + 1])
+                .filter(|&&alive| alive)
+                .count() as u8
+                - cells[y][x] as u8;
+            if self.rule.next_state(cells[y][x], neighbors) {
+                *cell = ALIVE;
+            }
+        }
+        let [nw, ne, sw, se] = next;
+        self.node(nw, ne, sw, se)
+    }
+
+    /// Rebuilds the node store with only the nodes reachable from the root.
+    fn collect_garbage(&mut self) {
+        let mut fresh = HashLife::new(self.rule);
+        let mut mapping = HashMap::new();
+        fresh.root = fresh.copy_node(self, self.root, &mut mapping);
+        fresh.origin = self.origin;
+        fresh.step_log = self.step_log;
+        fresh.generation = self.generation;
+        *self = fresh;
+    }
+
+    fn copy_node(&mut self, from: &HashLife, id: NodeId, mapping: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if id == DEAD || id == ALIVE {
+            return id;
+        }
+        if let Some(&copied) = mapping.get(&id) {
+            return copied;
+        }
+        let [nw, ne, sw, se] = from.nodes[id as usize].children.map(|child| self.copy_node(from, child, mapping));
+        let copied = self.node(nw, ne, sw, se);
+        mapping.insert(id, copied);
+        copied
+    }
+}
+
+fn hash_mul(a: u64, b: u64) -> u64 {
+    (a as u128 * b as u128 % HASH_MODULUS as u128) as u64
+}
+
+/// `base^exponent` modulo [`HASH_MODULUS`], where negative exponents give the inverse.
+fn hash_pow(base: u64, exponent: i64) -> u64 {
+    // the multiplicative group has order HASH_MODULUS - 1
+    let mut exponent = exponent.rem_euclid(HASH_MODULUS as i64 - 1) as u64;
+    let (mut base, mut power) = (base, 1);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            power = hash_mul(power, base);
+        }
+        base = hash_mul(base, base);
+        exponent >>= 1;
+    }
+    power
+}
+
+const fn hash_shifts(base: u64) -> [u64; 64] {
+    let mut shifts = [base % HASH_MODULUS; 64];
+    let mut k = 1;
+    while k < 64 {
+        shifts[k] = (shifts[k - 1] as u128 * shifts[k - 1] as u128 % HASH_MODULUS as u128) as u64;
+        k += 1;
+    }
+    shifts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HashLife, PlaneOverflow, MAX_STEP_LOG};
+    use crate::rule::Rule;
+
+    fn glider(life: &mut HashLife, x: i64, y: i64) {
+        for (dx, dy) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            life.set_alive(x + dx, y + dy, true);
+        }
+    }
+
+    #[test]
+    fn state_hash_depends_on_cells_only() {
+        let rule = Rule::CONWAY;
+        let mut life = HashLife::new(rule);
+        glider(&mut life, 0, 0);
+        let start = life.state_hash();
+        for _ in 0..4 {
+            life.step().unwrap();
+        }
+        // the same glider one cell further, in a quadtree grown and recentered differently
+        let mut moved = HashLife::new(rule);
+        moved.set_alive(-300, 500, true);
+        moved.set_alive(-300, 500, false);
+        glider(&mut moved, 1, 1);
+        assert_eq!(life.state_hash(), moved.state_hash());
+        assert_ne!(life.state_hash(), start);
+
+        let mut empty = HashLife::new(rule);
+        assert_eq!(empty.state_hash(), HashLife::new(rule).state_hash());
+        empty.set_alive(-1 << 40, 1 << 40, true);
+        assert_ne!(empty.state_hash(), HashLife::new(rule).state_hash());
+    }
+
+    #[test]
+    fn overflowing_the_plane_is_an_error() {
+        let mut life = HashLife::new(Rule::CONWAY);
+        glider(&mut life, 0, 0);
+        life.set_step_log(MAX_STEP_LOG);
+        let overflow = loop {
+            let (generation, hash) = (life.generation(), life.state_hash());
+            if let Err(overflow) = life.step() {
+                assert_eq!((life.generation(), life.state_hash()), (generation, hash));
+                break overflow;
+            }
+            assert!(life.generation() > generation);
+        };
+        assert_eq!(overflow, PlaneOverflow);
+        assert_eq!(life.population(), 5);
+    }
+}
@@ -0,0 +1,37 @@
+//! Conway's Game of Life, and other life-like automata, simulation core.
+//!
+//! The windowed binary is a thin consumer of this crate; everything needed to seed, step and
+//! inspect a board lives here and does not depend on winit or pixels.
+
+pub mod bitgrid;
+pub mod census;
+pub mod cycle;
+pub mod grid;
+pub mod hashlife;
+pub mod palette;
+mod parallel;
+pub mod pattern;
+pub mod render;
+pub mod rule;
+pub mod seeding;
+pub mod snapshot;
+pub mod sparse;
+pub mod stats;
+pub mod topology;
+pub mod universe;
+
+pub use bitgrid::BitGrid;
+pub use census::{Census, Object, ObjectKind};
+pub use cycle::{Cycle, CycleDetector};
+pub use grid::Grid;
+pub use hashlife::{HashLife, PlaneOverflow};
+pub use palette::{Color, Coloring, Gradient, Palette, PaletteError, ParseColoringError};
+pub use pattern::{Pattern, PatternError};
+pub use render::{render, Camera};
+pub use rule::{ParseRuleError, Rule};
+pub use seeding::{ParseSeedingError, Seeding, Symmetry};
+pub use snapshot::SnapshotError;
+pub use sparse::SparseUniverse;
+pub use stats::{Changes, History, Stats, StatsFormat, StatsWriter};
+pub use topology::{ParseTopologyError, Topology};
+pub use universe::{Engine, ParseEngineError, Universe, DEFAULT_DENSITY, HEIGHT, WIDTH};
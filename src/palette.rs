@@ -0,0 +1,275 @@
+//! Colors for drawing cells, and loading them from a palette file.
+//!
+//! A palette file holds `key = value` lines; blank lines and lines starting with `#` are
+//! ignored, and keys that are left out keep their default:
+//!
+//! ```text
+//! coloring = age
+//! background = #000000
+//! live = #ffffff
+//! trail = #4060ff
+//! born = #40ff40
+//! died = #ff4040
+//! gradient = #3050ff, #30e0ff, #40ff60, #ffe030, #ff3030
+//! max_age = 100
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// An RGB color.
+pub type Color = [u8; 3];
+
+/// What decides the color of a live cell. Dead cells are drawn the same way by all of them,
+/// fading from the trail color to the background as their trail decays.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Coloring {
+    /// Every live cell in the live color.
+    #[default]
+    Fade,
+    /// Along the gradient by how many generations the cell has been alive, reaching its end at
+    /// `max_age`.
+    Age,
+    /// Along the gradient by the number of live neighbors, from 0 to 8.
+    Neighbors,
+    /// Cells born this generation in the born color and cells that died in the died color.
+    Changes,
+}
+
+impl Coloring {
+    pub const ALL: [Coloring; 4] = [Coloring::Fade, Coloring::Age, Coloring::Neighbors, Coloring::Changes];
+
+    /// The coloring after this one in [`Coloring::ALL`], wrapping around.
+    pub fn next(self) -> Coloring {
+        let index = Coloring::ALL.iter().position(|&coloring| coloring == self).unwrap_or(0);
+        Coloring::ALL[(index + 1) % Coloring::ALL.len()]
+    }
+
+    /// Whether the universe has to track cell ages, see [`Universe::set_track_ages`].
+    ///
+    /// [`Universe::set_track_ages`]: crate::universe::Universe::set_track_ages
+    pub fn needs_ages(self) -> bool {
+        matches!(self, Coloring::Age | Coloring::Changes)
+    }
+}
+
+impl fmt::Display for Coloring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Coloring::Fade => write!(f, "fade"),
+            Coloring::Age => write!(f, "age"),
+            Coloring::Neighbors => write!(f, "neighbors"),
+            Coloring::Changes => write!(f, "changes"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseColoringError(String);
+
+impl fmt::Display for ParseColoringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown coloring '{}', expected fade, age, neighbors or changes", self.0)
+    }
+}
+
+impl Error for ParseColoringError {}
+
+impl FromStr for Coloring {
+    type Err = ParseColoringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fade" => Ok(Coloring::Fade),
+            "age" => Ok(Coloring::Age),
+            "neighbors" | "neighbours" => Ok(Coloring::Neighbors),
+            "changes" => Ok(Coloring::Changes),
+            _ => Err(ParseColoringError(s.to_string())),
+        }
+    }
+}
+
+/// Colors spread evenly from 0 to 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gradient(Vec<Color>);
+
+impl Gradient {
+    /// Creates a gradient through `stops`. Without stops everything is black.
+    pub fn new(stops: Vec<Color>) -> Self {
+        Gradient(stops)
+    }
+
+    pub fn stops(&self) -> &[Color] {
+        &self.0
+    }
+
+    /// The color at `t`, clamped to `0.0..=1.0`.
+    pub fn at(&self, t: f64) -> Color {
+        match self.0.as_slice() {
+            [] => [0; 3],
+            [color] => *color,
+            stops => {
+                let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+                let index = (position as usize).min(stops.len() - 2);
+                mix(stops[index], stops[index + 1], position - index as f64)
+            }
+        }
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Gradient::new(vec![[0x30, 0x50, 0xff], [0x30, 0xe0, 0xff], [0x40, 0xff, 0x60], [0xff, 0xe0, 0x30], [0xff, 0x30, 0x30]])
+    }
+}
+
+/// How to color the cells of a frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub coloring: Coloring,
+    pub background: Color,
+    /// Live cells, unless the coloring uses the gradient.
+    pub live: Color,
+    /// Dead cells that were alive recently, at full strength right after they died.
+    pub trail: Color,
+    pub born: Color,
+    pub died: Color,
+    pub gradient: Gradient,
+    /// Age in generations at which [`Coloring::Age`] reaches the end of the gradient.
+    pub max_age: u16,
+}
+
+impl Default for Palette {
+    /// White cells with white trails on black, which looks like plain grayscale.
+    fn default() -> Self {
+        Palette {
+            coloring: Coloring::Fade,
+            background: [0x00, 0x00, 0x00],
+            live: [0xff, 0xff, 0xff],
+            trail: [0xff, 0xff, 0xff],
+            born: [0x40, 0xff, 0x40],
+            died: [0xff, 0x40, 0x40],
+            gradient: Gradient::default(),
+            max_age: 100,
+        }
+    }
+}
+
+impl Palette {
+    /// Reads a palette file, see the [module documentation](self) for its format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PaletteError> {
+        Palette::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses the contents of a palette file, see the [module documentation](self).
+    pub fn parse(source: &str) -> Result<Self, PaletteError> {
+        let mut palette = Palette::default();
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let malformed = |message: String| PaletteError::Malformed { line: index + 1, message };
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| malformed(format!("expected 'key = value', found '{}'", line)))?;
+            let value = value.trim();
+            let color = || parse_color(value).map_err(malformed);
+            match key.trim().to_ascii_lowercase().as_str() {
+                "coloring" => palette.coloring = value.parse().map_err(|error: ParseColoringError| malformed(error.to_string()))?,
+                "background" => palette.background = color()?,
+                "live" => palette.live = color()?,
+                "trail" => palette.trail = color()?,
+                "born" => palette.born = color()?,
+                "died" => palette.died = color()?,
+                "gradient" => {
+                    let stops = value.split(',').map(|stop| parse_color(stop.trim())).collect::<Result<_, _>>();
+                    palette.gradient = Gradient::new(stops.map_err(malformed)?);
+                }
+                "max_age" => {
+                    palette.max_age = value
+                        .parse()
+                        .map_err(|_| malformed(format!("invalid max_age '{}', expected a number of generations", value)))?;
+                }
+                key => return Err(malformed(format!("unknown key '{}'", key))),
+            }
+        }
+        Ok(palette)
+    }
+
+    pub fn with_coloring(mut self, coloring: Coloring) -> Self {
+        self.coloring = coloring;
+        self
+    }
+
+    /// The RGBA color of a cell, always opaque. `fade` is the strength of its trail, `age` as
+    /// returned by [`Universe::age`] if known, and `neighbors` its number of live neighbors.
+    ///
+    /// [`Universe::age`]: crate::universe::Universe::age
+    pub fn color(&self, alive: bool, fade: u8, age: Option<i16>, neighbors: u8) -> [u8; 4] {
+        let [r, g, b] = match (self.coloring, alive) {
+            (Coloring::Changes, false) if age == Some(-1) => self.died,
+            (_, false) => mix(self.background, self.trail, fade as f64 / 255.0),
+            (Coloring::Fade, true) => self.live,
+            (Coloring::Age, true) => {
+                let age = age.unwrap_or(1).max(1) - 1;
+                self.gradient.at(age as f64 / self.max_age.max(1) as f64)
+            }
+            (Coloring::Neighbors, true) => self.gradient.at(neighbors as f64 / 8.0),
+            (Coloring::Changes, true) if age == Some(1) => self.born,
+            (Coloring::Changes, true) => self.live,
+        };
+        [r, g, b, 0xff]
+    }
+}
+
+#[derive(Debug)]
+pub enum PaletteError {
+    Io(io::Error),
+    /// A line of the file is not understood, with its 1-based number.
+    Malformed { line: usize, message: String },
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::Io(error) => write!(f, "could not read palette: {}", error),
+            PaletteError::Malformed { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for PaletteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PaletteError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PaletteError {
+    fn from(error: io::Error) -> Self {
+        PaletteError::Io(error)
+    }
+}
+
+/// Parses `#rrggbb`, with or without the `#`.
+fn parse_color(s: &str) -> Result<Color, String> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    let channel = |i: usize| hex.get(i..i + 2).and_then(|digits| u8::from_str_radix(digits, 16).ok());
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok([r, g, b]),
+        _ => Err(format!("invalid color '{}', expected #rrggbb", s)),
+    }
+}
+
+/// The color `t` of the way from `from` to `to`.
+fn mix(from: Color, to: Color, t: f64) -> Color {
+    let channel = |i: usize| (from[i] as f64 + (to[i] as f64 - from[i] as f64) * t).round() as u8;
+    [channel(0), channel(1), channel(2)]
+}
@@ -0,0 +1,66 @@
+use std::ops::Range;
+use std::thread;
+
+use crate::stats::Changes;
+
+/// Splits `out`, a buffer of `height` rows of `row_len` elements each, into up to `threads`
+/// horizontal bands and calls `step_band` for each band on its own scoped thread.
+///
+/// `step_band` receives the range of rows it is responsible for and the matching slice of `out`,
+/// and returns the cells born and died in that band. Bands are contiguous and only read shared
+/// state, so the result is identical to calling `step_band(0..height, out)` on one thread.
+pub(crate) fn step_in_bands<T, F>(out: &mut [T], row_len: usize, height: usize, threads: usize, step_band: F) -> Changes
+where
+    T: Send,
+    F: Fn(Range<usize>, &mut [T]) -> Changes + Sync,
+{
+    let threads = threads.clamp(1, height);
+    if threads == 1 {
+        return step_band(0..height, out);
+    }
+
+    let band_rows = height.div_ceil(threads);
+    thread::scope(|scope| {
+        let step_band = &step_band;
+        let workers: Vec<_> = out
+            .chunks_mut(band_rows * row_len)
+            .enumerate()
+            .map(|(band, out)| {
+                let start = band * band_rows;
+                let rows = start..start + out.len() / row_len;
+                scope.spawn(move || step_band(rows, out))
+            })
+            .collect();
+        workers
+            .into_iter()
+            .fold(Changes::default(), |changes, worker| changes + worker.join().expect("worker thread panicked"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::step_in_bands;
+    use crate::stats::Changes;
+
+    #[test]
+    fn bands_cover_every_row_once() {
+        let row_len = 3;
+        for height in 1..=20 {
+            for threads in [0, 1, 2, 3, 7, 8, 64] {
+                let mut out = vec![usize::MAX; row_len * height];
+                let changes = step_in_bands(&mut out, row_len, height, threads, |rows, out| {
+                    assert_eq!(out.len(), rows.len() * row_len);
+                    for (row, y) in out.chunks_mut(row_len).zip(rows.clone()) {
+                        row.fill(y);
+                    }
+                    Changes { births: rows.len(), deaths: 1 }
+                });
+                let rows: Vec<usize> = out.chunks(row_len).map(|row| row[0]).collect();
+                assert_eq!(rows, (0..height).collect::<Vec<_>>(), "height {} on {} threads", height, threads);
+                assert_eq!(changes.births, height, "height {} on {} threads", height, threads);
+                // one death per band, and never more bands than threads
+                assert!((1..=threads.clamp(1, height)).contains(&changes.deaths), "height {} on {} threads", height, threads);
+            }
+        }
+    }
+}
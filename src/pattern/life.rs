@@ -0,0 +1,131 @@
+use super::{Pattern, PatternError};
+use crate::rule::Rule;
+
+/// Parses the Life 1.05 and Life 1.06 formats, telling them apart by their `#Life` header.
+pub(super) fn parse(source: &str) -> Result<Pattern, PatternError> {
+    let header = source.lines().next().unwrap_or("").trim();
+    match header {
+        "#Life 1.05" => parse_105(source),
+        "#Life 1.06" => parse_106(source),
+        _ => Err(PatternError::malformed(1, "expected a '#Life 1.05' or '#Life 1.06' header")),
+    }
+}
+
+/// Life 1.05: `#D` description lines, an optional `#N` (normal Conway rule) or `#R s/b` rule
+/// line, and blocks of `.`/`*` rows each introduced by a `#P x y` top-left position.
+fn parse_105(source: &str) -> Result<Pattern, PatternError> {
+    let mut comments = Vec::new();
+    let mut rule = None;
+    let mut cells = Vec::new();
+    // top-left corner of the current block and the row within it
+    let (mut block_x, mut block_y, mut row) = (0i64, 0i64, 0usize);
+    for (number, line) in source.lines().enumerate().skip(1).map(|(i, line)| (i + 1, line.trim())) {
+        if let Some(directive) = line.strip_prefix('#') {
+            let mut chars = directive.chars();
+            let kind = chars.next();
+            let text = chars.as_str().trim();
+            match kind {
+                Some('D') | Some('C') => comments.push(text.to_string()),
+                Some('N') => rule = Some(Rule::CONWAY),
+                Some('R') => {
+                    let parsed = text.parse().map_err(|error| PatternError::malformed(number, format!("invalid rule: {}", error)))?;
+                    rule = Some(parsed);
+                }
+                Some('P') => {
+                    (block_x, block_y) = parse_coordinates(number, text)?;
+                    row = 0;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                '*' => {
+                    let offset = |start: i64, offset: usize| i64::try_from(offset).ok().and_then(|offset| start.checked_add(offset));
+                    match (offset(block_x, x), offset(block_y, row)) {
+                        (Some(x), Some(y)) => cells.push((number, (x, y))),
+                        _ => return Err(PatternError::malformed(number, "cell beyond the coordinate range")),
+                    }
+                }
+                c => return Err(PatternError::malformed(number, format!("unexpected '{}', expected '.' or '*'", c))),
+            }
+        }
+        row += 1;
+    }
+
+    let mut pattern = Pattern::from_signed_cells(cells)?;
+    pattern.comments = comments;
+    pattern.rule = rule;
+    Ok(pattern)
+}
+
+/// Life 1.06: one `x y` coordinate pair per alive cell.
+fn parse_106(source: &str) -> Result<Pattern, PatternError> {
+    let mut cells = Vec::new();
+    for (number, line) in source.lines().enumerate().skip(1).map(|(i, line)| (i + 1, line.trim())) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        cells.push((number, parse_coordinates(number, line)?));
+    }
+    Pattern::from_signed_cells(cells)
+}
+
+fn parse_coordinates(number: usize, text: &str) -> Result<(i64, i64), PatternError> {
+    let mut parts = text.split_whitespace().map(|part| part.parse::<i64>());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Ok((x, y)),
+        _ => Err(PatternError::malformed(number, format!("expected '<x> <y>', got '{}'", text))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pattern::{Pattern, PatternError};
+    use crate::rule::Rule;
+
+    fn error_line(source: &str) -> usize {
+        match Pattern::from_life(source) {
+            Err(PatternError::Malformed { line, .. }) => line,
+            other => panic!("expected a malformed pattern, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_life_105_blocks() {
+        let pattern = Pattern::from_life("#Life 1.05\n#D Two blocks\n#N\n#P -1 -1\n.*\n*.*\n#P 5 5\n**\n").unwrap();
+        assert_eq!(pattern.comments(), ["Two blocks"]);
+        assert_eq!(pattern.rule(), Some(Rule::CONWAY));
+        assert_eq!((pattern.width(), pattern.height()), (8, 7));
+        assert_eq!(pattern.cells(), [(1, 0), (0, 1), (2, 1), (6, 6), (7, 6)]);
+
+        let pattern = Pattern::from_life("#Life 1.05\n#R 23/36\n*\n").unwrap();
+        assert_eq!(pattern.rule(), Some("B36/S23".parse().unwrap()));
+    }
+
+    #[test]
+    fn parses_life_106_coordinates() {
+        let pattern = Pattern::from_life("#Life 1.06\n0 -1\n1 0\n# a comment\n-1 1\n0 1\n1 1\n").unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (3, 3));
+        assert_eq!(pattern.cells(), [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn reports_malformed_patterns() {
+        assert_eq!(error_line("#Life 2.0\n0 0\n"), 1);
+        assert_eq!(error_line("#Life 1.05\n*x\n"), 2);
+        assert_eq!(error_line("#Life 1.05\n#R 23/9\n*\n"), 2);
+        assert_eq!(error_line("#Life 1.06\n0 0\n0\n"), 3);
+        assert_eq!(error_line("#Life 1.06\n0 0 0\n"), 2);
+        assert_eq!(error_line("#Life 1.06\n-9223372036854775808 0\n9223372036854775807 0\n"), 3);
+        assert_eq!(error_line("#Life 1.05\n#P 9223372036854775806 0\n..*\n"), 3);
+        // the last representable row is fine, the one after it is not
+        assert!(Pattern::from_life("#Life 1.05\n#P 0 9223372036854775807\n*\n").is_ok());
+        assert_eq!(error_line("#Life 1.05\n#P 0 9223372036854775807\n*\n*\n"), 4);
+    }
+}
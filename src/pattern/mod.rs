@@ -0,0 +1,232 @@
+//! Loading and placing patterns from the common Life file formats.
+
+mod life;
+mod plaintext;
+mod rle;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::rule::Rule;
+
+/// Most alive cells a pattern file may expand to. Run lengths let a few bytes of RLE describe an
+/// arbitrary number of cells, so larger patterns are rejected rather than allocated.
+pub const MAX_CELLS: usize = 1 << 26;
+
+/// A finite set of alive cells with its bounding size, plus the metadata a pattern file carried.
+///
+/// Cell coordinates are relative to the pattern's top-left corner.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pattern {
+    name: Option<String>,
+    comments: Vec<String>,
+    rule: Option<Rule>,
+    width: usize,
+    height: usize,
+    cells: Vec<(usize, usize)>,
+}
+
+impl Pattern {
+    /// Builds a pattern from alive cell coordinates, sizing it to fit them.
+    pub fn from_cells(cells: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let cells: Vec<_> = cells.into_iter().collect();
+        let width = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+        Pattern {
+            width,
+            height,
+            cells,
+            ..Pattern::default()
+        }
+    }
+
+    /// Builds a pattern from signed coordinates, as used by the coordinate-list formats, shifting
+    /// them so the top-left alive cell lies in row 0 and column 0. Each cell comes with the line
+    /// it was read from, for reporting cells too far apart to fit a pattern.
+    fn from_signed_cells(cells: Vec<(usize, (i64, i64))>) -> Result<Self, PatternError> {
+        let min_x = cells.iter().map(|&(_, (x, _))| x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|&(_, (_, y))| y).min().unwrap_or(0);
+        let offset = |value: i64, min: i64| value.checked_sub(min).and_then(|offset| usize::try_from(offset).ok());
+        let cells = cells.into_iter().map(|(number, (x, y))| match (offset(x, min_x), offset(y, min_y)) {
+            (Some(x), Some(y)) => Ok((x, y)),
+            _ => Err(PatternError::malformed(number, format!("cell {} {} is too far from the others", x, y))),
+        });
+        Ok(Pattern::from_cells(cells.collect::<Result<Vec<_>, _>>()?))
+    }
+
+    /// Parses a Run Length Encoded (`.rle`) pattern.
+    pub fn from_rle(source: &str) -> Result<Self, PatternError> {
+        rle::parse(source)
+    }
+
+    /// Parses a plaintext (`.cells`) pattern.
+    pub fn from_plaintext(source: &str) -> Result<Self, PatternError> {
+        plaintext::parse(source)
+    }
+
+    /// Parses a Life 1.05 or Life 1.06 (`.lif`, `.life`) pattern.
+    pub fn from_life(source: &str) -> Result<Self, PatternError> {
+        life::parse(source)
+    }
+
+    /// Reads a pattern file, picking the format from its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PatternError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("");
+        match extension.to_ascii_lowercase().as_str() {
+            "rle" => Pattern::from_rle(&source),
+            "cells" => Pattern::from_plaintext(&source),
+            "lif" | "life" => Pattern::from_life(&source),
+            _ => Err(PatternError::UnknownFormat(path.display().to_string())),
+        }
+    }
+
+    /// Encodes the pattern as RLE.
+    pub fn to_rle(&self) -> String {
+        rle::write(self)
+    }
+
+    /// Encodes the pattern as plaintext (`.cells`).
+    pub fn to_plaintext(&self) -> String {
+        plaintext::write(self)
+    }
+
+    /// Writes the pattern to a file, picking the format from its extension (`.rle` or `.cells`).
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PatternError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("");
+        let contents = match extension.to_ascii_lowercase().as_str() {
+            "rle" => self.to_rle(),
+            "cells" => self.to_plaintext(),
+            _ => return Err(PatternError::UnknownFormat(path.display().to_string())),
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Sets the bounding size, e.g. to keep empty margins. It never shrinks below the cells.
+    pub fn with_size(mut self, width: usize, height: usize) -> Self {
+        self.width = self.width.max(width);
+        self.height = self.height.max(height);
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rule = Some(rule);
+        self
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comments.push(comment.into());
+        self
+    }
+
+    /// The part of the pattern in the `width` x `height` rectangle with its top-left corner at
+    /// `(x, y)`, sized to the rectangle and keeping the name, comments and rule.
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Pattern {
+        let inside = |&&(cx, cy): &&(usize, usize)| cx >= x && cy >= y && cx - x < width && cy - y < height;
+        Pattern {
+            name: self.name.clone(),
+            comments: self.comments.clone(),
+            rule: self.rule,
+            width,
+            height,
+            cells: self.cells.iter().filter(inside).map(|&(cx, cy)| (cx - x, cy - y)).collect(),
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// The rule the pattern file asked for, if it named one.
+    pub fn rule(&self) -> Option<Rule> {
+        self.rule
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Coordinates of the alive cells, relative to the top-left corner.
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// The sorted x coordinates of the alive cells in each row.
+    fn rows(&self) -> Vec<Vec<usize>> {
+        let mut rows = vec![Vec::new(); self.height];
+        for &(x, y) in &self.cells {
+            rows[y].push(x);
+        }
+        for row in &mut rows {
+            row.sort_unstable();
+            row.dedup();
+        }
+        rows
+    }
+}
+
+#[derive(Debug)]
+pub enum PatternError {
+    Io(io::Error),
+    /// The file extension does not name a supported pattern format.
+    UnknownFormat(String),
+    /// The contents do not follow the format, with the 1-based line the problem was found on.
+    Malformed { line: usize, message: String },
+}
+
+impl PatternError {
+    pub(crate) fn malformed(line: usize, message: impl Into<String>) -> Self {
+        PatternError::Malformed {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Io(error) => write!(f, "could not read pattern: {}", error),
+            PatternError::UnknownFormat(path) => write!(f, "unknown pattern format for '{}'", path),
+            PatternError::Malformed { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for PatternError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PatternError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PatternError {
+    fn from(error: io::Error) -> Self {
+        PatternError::Io(error)
+    }
+}
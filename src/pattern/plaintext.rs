@@ -0,0 +1,88 @@
+use super::{Pattern, PatternError};
+
+/// Parses the plaintext (`.cells`) format: `!` comment lines, optionally `!Name: ...` and
+/// `!Rule: ...`, followed by one line per row with `.` for dead and `O` (or `*`) for alive cells.
+pub(super) fn parse(source: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::default();
+    let mut y = 0;
+    for (number, line) in source.lines().enumerate().map(|(i, line)| (i + 1, line.trim_end())) {
+        if let Some(comment) = line.strip_prefix('!') {
+            if let Some(name) = comment.strip_prefix("Name:") {
+                pattern.name = Some(name.trim().to_string());
+            } else if let Some(rule) = comment.strip_prefix("Rule:") {
+                let rule = rule.trim().parse().map_err(|error| PatternError::malformed(number, format!("invalid rule: {}", error)))?;
+                pattern.rule = Some(rule);
+            } else {
+                pattern.comments.push(comment.trim().to_string());
+            }
+            continue;
+        }
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' | '*' => pattern.cells.push((x, y)),
+                c => return Err(PatternError::malformed(number, format!("unexpected '{}', expected '.' or 'O'", c))),
+            }
+        }
+        pattern.width = pattern.width.max(line.chars().count());
+        y += 1;
+    }
+    pattern.height = y;
+    Ok(pattern)
+}
+
+/// Encodes a pattern as plaintext, with its name and comments as `!` lines. The format has no
+/// rule field, so the rule is written as a `!Rule:` comment.
+pub(super) fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    if let Some(name) = &pattern.name {
+        out.push_str(&format!("!Name: {}\n", name));
+    }
+    if let Some(rule) = pattern.rule {
+        out.push_str(&format!("!Rule: {}\n", rule));
+    }
+    for comment in &pattern.comments {
+        out.push_str(&format!("!{}\n", comment));
+    }
+    for row in pattern.rows() {
+        let mut line = vec!['.'; row.last().map_or(0, |&x| x + 1)];
+        for &x in &row {
+            line[x] = 'O';
+        }
+        out.extend(line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pattern::{Pattern, PatternError};
+
+    #[test]
+    fn parses_names_rules_and_rows() {
+        let pattern = Pattern::from_plaintext("!Name: Glider\n!Rule: B36/S23\n!A small spaceship\n.O\n..*\nOOO\n").unwrap();
+        assert_eq!(pattern.name(), Some("Glider"));
+        assert_eq!(pattern.rule(), Some("B36/S23".parse().unwrap()));
+        assert_eq!(pattern.comments(), ["A small spaceship"]);
+        assert_eq!((pattern.width(), pattern.height()), (3, 3));
+        assert_eq!(pattern.cells(), [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn round_trips_through_the_writer() {
+        let pattern = Pattern::from_plaintext("!Name: Beehive\n!Rule: B3/S23\n.OO\nO..O\n.OO\n\n").unwrap();
+        assert_eq!(pattern.height(), 4);
+        assert_eq!(Pattern::from_plaintext(&pattern.to_plaintext()).unwrap(), pattern);
+    }
+
+    #[test]
+    fn reports_malformed_patterns() {
+        for (source, expected) in [(".O\nOx\n", 2), ("!Rule: B3/S9\nO\n", 1)] {
+            match Pattern::from_plaintext(source) {
+                Err(PatternError::Malformed { line, .. }) => assert_eq!(line, expected, "{:?}", source),
+                other => panic!("expected {:?} to be malformed, got {:?}", source, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use super::{Pattern, PatternError, MAX_CELLS};
+use crate::rule::Rule;
+
+/// Parses the Run Length Encoded format: `#` comment lines, an `x = m, y = n, rule = ...` header
+/// and runs of `b` (dead), `o` (alive) and `$` (end of row) terminated by `!`.
+pub(super) fn parse(source: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::default();
+    let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line.trim()));
+
+    let mut header = None;
+    for (number, line) in lines.by_ref() {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next();
+            let text = chars.as_str().trim();
+            match kind {
+                Some('N') => pattern.name = Some(text.to_string()),
+                Some('C') | Some('c') | Some('O') => pattern.comments.push(text.to_string()),
+                Some('r') => pattern.rule = Some(parse_rule(number, text)?),
+                _ => {}
+            }
+            continue;
+        }
+        header = Some((number, line));
+        break;
+    }
+    let (number, header) = header.ok_or_else(|| PatternError::malformed(1, "missing 'x = ..., y = ...' header"))?;
+    parse_header(number, header, &mut pattern)?;
+
+    let (mut x, mut y): (usize, usize) = (0, 0);
+    let too_far = |number: usize| PatternError::malformed(number, "position beyond the largest supported size");
+    let mut count: Option<usize> = None;
+    let mut finished = false;
+    'body: for (number, line) in lines {
+        for c in line.chars() {
+            match c {
+                '0'..='9' => {
+                    let digit = c as usize - '0' as usize;
+                    let run = count.unwrap_or(0).checked_mul(10).and_then(|run| run.checked_add(digit));
+                    count = Some(run.ok_or_else(|| PatternError::malformed(number, "run count too large"))?);
+                    continue;
+                }
+                'b' => x = x.checked_add(count.unwrap_or(1)).ok_or_else(|| too_far(number))?,
+                'o' => {
+                    let end = x.checked_add(count.unwrap_or(1)).ok_or_else(|| too_far(number))?;
+                    if end > pattern.width || y >= pattern.height {
+                        return Err(PatternError::malformed(number, format!("cells beyond the {}x{} header size", pattern.width, pattern.height)));
+                    }
+                    if end - x > MAX_CELLS - pattern.cells.len() {
+                        return Err(PatternError::malformed(number, format!("more than {} alive cells", MAX_CELLS)));
+                    }
+                    pattern.cells.extend((x..end).map(|x| (x, y)));
+                    x = end;
+                }
+                '$' => {
+                    y = y.checked_add(count.unwrap_or(1)).ok_or_else(|| too_far(number))?;
+                    x = 0;
+                }
+                '!' => {
+                    finished = true;
+                    break 'body;
+                }
+                c if c.is_whitespace() => {}
+                c => return Err(PatternError::malformed(number, format!("unexpected '{}' in pattern data", c))),
+            }
+            count = None;
+        }
+    }
+    if !finished {
+        return Err(PatternError::malformed(source.lines().count(), "pattern data is not terminated by '!'"));
+    }
+
+    Ok(pattern)
+}
+
+/// Reads `x = m, y = n` and the optional `rule = ...` into the pattern.
+fn parse_header(number: usize, header: &str, pattern: &mut Pattern) -> Result<(), PatternError> {
+    let (mut width, mut height) = (None, None);
+    // the rule may contain commas itself, as in Golly's bounded grids like `B3/S23:T20,20`
+    let (sizes, rule) = match header.find("rule") {
+        Some(start) => (&header[..start], Some(&header[start..])),
+        None => (header, None),
+    };
+    for field in sizes.split(',').filter(|field| !field.trim().is_empty()).chain(rule) {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| PatternError::malformed(number, format!("expected 'key = value' in header, got '{}'", field.trim())))?;
+        let value = value.trim();
+        let size = || value.parse::<usize>().map_err(|_| PatternError::malformed(number, format!("invalid size '{}'", value)));
+        match key.trim() {
+            "x" => width = Some(size()?),
+            "y" => height = Some(size()?),
+            "rule" => pattern.rule = Some(parse_rule(number, value)?),
+            _ => {}
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => {
+            pattern.width = width;
+            pattern.height = height;
+            Ok(())
+        }
+        _ => Err(PatternError::malformed(number, "header must contain both x and y")),
+    }
+}
+
+/// Parses a rule, ignoring Golly's `:T...` bounded grid suffix.
+fn parse_rule(number: usize, rule: &str) -> Result<Rule, PatternError> {
+    let rule = rule.split(':').next().unwrap_or(rule);
+    rule.parse().map_err(|error| PatternError::malformed(number, format!("invalid rule: {}", error)))
+}
+
+/// Longest line emitted when encoding, as recommended by the format.
+const LINE_LENGTH: usize = 70;
+
+/// Encodes a pattern as RLE, with its name and comments as `#N` and `#C` lines.
+pub(super) fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    if let Some(name) = &pattern.name {
+        out.push_str(&format!("#N {}\n", name));
+    }
+    for comment in &pattern.comments {
+        out.push_str(&format!("#C {}\n", comment));
+    }
+    out.push_str(&format!("x = {}, y = {}", pattern.width, pattern.height));
+    if let Some(rule) = pattern.rule {
+        out.push_str(&format!(", rule = {}", rule));
+    }
+    out.push('\n');
+
+    let mut runs: Vec<(usize, char)> = Vec::new();
+    let mut push = |count: usize, tag: char| match runs.last_mut() {
+        Some((run, last)) if *last == tag => *run += count,
+        _ if count > 0 => runs.push((count, tag)),
+        _ => {}
+    };
+    for (y, row) in pattern.rows().iter().enumerate() {
+        if y > 0 {
+            push(1, '$');
+        }
+        let mut x = 0;
+        for &alive_x in row {
+            push(alive_x - x, 'b');
+            push(1, 'o');
+            x = alive_x + 1;
+        }
+    }
+    // the header already tells the height, so trailing empty rows are implied
+    if matches!(runs.last(), Some((_, '$'))) {
+        runs.pop();
+    }
+
+    let mut line = String::new();
+    for (count, tag) in runs {
+        let run = if count == 1 { tag.to_string() } else { format!("{}{}", count, tag) };
+        if line.len() + run.len() > LINE_LENGTH {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        line.push_str(&run);
+    }
+    if line.len() + 1 > LINE_LENGTH {
+        out.push_str(&line);
+        out.push('\n');
+        line.clear();
+    }
+    line.push('!');
+    out.push_str(&line);
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pattern::{Pattern, PatternError, MAX_CELLS};
+    use crate::rule::Rule;
+
+    /// The line a malformed pattern is reported on.
+    fn error_line(source: &str) -> usize {
+        match Pattern::from_rle(source) {
+            Err(PatternError::Malformed { line, .. }) => line,
+            other => panic!("expected a malformed pattern, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_header_comments_and_runs() {
+        let pattern = Pattern::from_rle("#N Glider\n#C A small spaceship\nx = 3, y = 3, rule = B36/S23\nbo$2b\no$3o!").unwrap();
+        assert_eq!(pattern.name(), Some("Glider"));
+        assert_eq!(pattern.comments(), ["A small spaceship"]);
+        assert_eq!(pattern.rule(), Some("B36/S23".parse().unwrap()));
+        assert_eq!((pattern.width(), pattern.height()), (3, 3));
+        assert_eq!(pattern.cells(), [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+
+        // Golly's bounded grid suffix is ignored and empty rows can be skipped with a count
+        let pattern = Pattern::from_rle("x = 2, y = 4, rule = B3/S23:T20,20\no2$bo!").unwrap();
+        assert_eq!(pattern.rule(), Some(Rule::CONWAY));
+        assert_eq!(pattern.cells(), [(0, 0), (1, 2)]);
+    }
+
+    #[test]
+    fn round_trips_through_the_writer() {
+        let source = "x = 36, y = 9, rule = B3/S23\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!";
+        let pattern = Pattern::from_rle(source).unwrap().with_name("Gosper glider gun").with_comment("period 30");
+        let written = pattern.to_rle();
+        assert!(written.lines().all(|line| line.len() <= 70), "{}", written);
+        assert_eq!(Pattern::from_rle(&written).unwrap(), pattern);
+    }
+
+    #[test]
+    fn reports_malformed_patterns() {
+        assert_eq!(error_line("#C only a comment\n"), 1);
+        assert_eq!(error_line("x = 3\n3o!"), 1);
+        assert_eq!(error_line("x = 3, y = three\n3o!"), 1);
+        assert_eq!(error_line("x = 3, y = 1, rule = B9/S23\n3o!"), 1);
+        assert_eq!(error_line("x = 3, y = 1\n4o!"), 2);
+        assert_eq!(error_line("x = 3, y = 1\no$o!"), 2);
+        assert_eq!(error_line("x = 3, y = 1\n\n3q!"), 3);
+        assert_eq!(error_line("x = 3, y = 1\n3o\n"), 2);
+        assert_eq!(error_line("x = 3, y = 1\n99999999999999999999999o!"), 2);
+    }
+
+    #[test]
+    fn rejects_runs_beyond_the_limits() {
+        assert_eq!(error_line("x = 1, y = 1\n18446744073709551615b2bo!"), 2);
+        assert_eq!(error_line("x = 1, y = 1\n18446744073709551615$18446744073709551615$o!"), 2);
+        let huge = format!("x = {}, y = 1\n{}o!", MAX_CELLS + 1, MAX_CELLS + 1);
+        assert_eq!(error_line(&huge), 2);
+        // a large header is fine as long as the cells stay within the budget
+        let sparse = Pattern::from_rle("x = 1000000000000, y = 1\no999999999998bo!").unwrap();
+        assert_eq!(sparse.cells(), [(0, 0), (999_999_999_999, 0)]);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::palette::{Coloring, Palette};
+use crate::universe::Universe;
+
+/// Most zoomed out level, where a pixel covers `2^-MIN_ZOOM` x `2^-MIN_ZOOM` cells.
+pub const MIN_ZOOM: i32 = -12;
+/// Most zoomed in level, where a cell is drawn as a `2^MAX_ZOOM` pixel block.
+pub const MAX_ZOOM: i32 = 6;
+
+/// The part of a universe shown in a frame and how large its cells are drawn.
+///
+/// Positions are board positions, which may lie beyond the board: the unbounded engines show
+/// their plane there. At zoom level `z` a cell is drawn as a `2^z` x `2^z` pixel block, and at
+/// negative levels every pixel covers `2^-z` x `2^-z` cells.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Camera {
+    /// Board position shown at the top-left corner of the frame, in cells.
+    x: f64,
+    y: f64,
+    zoom: i32,
+}
+
+impl Camera {
+    /// Creates a camera with `(x, y)` at the top-left corner of the frame. The zoom level is
+    /// clamped to `MIN_ZOOM..=MAX_ZOOM`.
+    pub fn new(x: f64, y: f64, zoom: i32) -> Self {
+        Camera {
+            x,
+            y,
+            zoom: zoom.clamp(MIN_ZOOM, MAX_ZOOM),
+        }
+    }
+
+    /// Board position shown at the top-left corner of the frame.
+    pub fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    pub fn zoom(&self) -> i32 {
+        self.zoom
+    }
+
+    /// Pixels per cell, below 1 when zoomed out.
+    pub fn scale(&self) -> f64 {
+        2f64.powi(self.zoom)
+    }
+
+    /// The board position drawn at pixel `(x, y)` of the frame.
+    pub fn cell_at(&self, x: f64, y: f64) -> (i64, i64) {
+        let scale = self.scale();
+        ((self.x + x / scale).floor() as i64, (self.y + y / scale).floor() as i64)
+    }
+
+    /// The pixel of the frame where the top-left corner of the cell at board position `(x, y)` is
+    /// drawn, possibly outside the frame.
+    pub fn pixel_at(&self, x: i64, y: i64) -> (f64, f64) {
+        let scale = self.scale();
+        ((x as f64 - self.x) * scale, (y as f64 - self.y) * scale)
+    }
+
+    /// Moves the picture by `(dx, dy)` pixels, as when dragging it.
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let scale = self.scale();
+        self.x -= dx / scale;
+        self.y -= dy / scale;
+    }
+
+    /// Zooms in by `levels`, or out if negative, keeping the cell under pixel `(x, y)` in place.
+    pub fn zoom_at(&mut self, x: f64, y: f64, levels: i32) {
+        let (old, new) = (self.scale(), 2f64.powi((self.zoom + levels).clamp(MIN_ZOOM, MAX_ZOOM)));
+        self.x += x / old - x / new;
+        self.y += y / old - y / new;
+        self.zoom = (self.zoom + levels).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+/// Writes the part of the universe seen by `camera` into an RGBA frame buffer `width` pixels
+/// wide, four bytes per pixel, colored with `palette`. Only the visible region of the universe
+/// is looked at.
+///
+/// Colorings by age need the universe to track ages, see [`Universe::set_track_ages`]. When
+/// zoomed out, neighbors are counted between the squares of cells drawn as one pixel.
+pub fn render(universe: &Universe, camera: &Camera, palette: &Palette, frame_buffer: &mut [u8], width: usize) {
+    let height = frame_buffer.len() / 4 / width.max(1);
+    if height == 0 {
+        return;
+    }
+    let shift = (-camera.zoom()).max(0) as u32;
+    let (left, top) = camera.cell_at(0.0, 0.0);
+    let (right, bottom) = camera.cell_at((width - 1) as f64, (height - 1) as f64);
+    // the view has a margin of one element all around for counting neighbors
+    let (left, top) = (left - (1 << shift), top - (1 << shift));
+    let view_width = ((right - left) >> shift) as usize + 2;
+    let view_height = ((bottom - top) >> shift) as usize + 2;
+    let view = universe.view(left, top, view_width, view_height, shift);
+    let ages = match palette.coloring.needs_ages() {
+        true => universe.view_ages(left, top, view_width, view_height, shift),
+        false => None,
+    };
+
+    let neighbors = |x: usize, y: usize| -> u8 {
+        let (x_range, y_range) = (x.saturating_sub(1)..(x + 2).min(view_width), y.saturating_sub(1)..(y + 2).min(view_height));
+        let alive = y_range.flat_map(|ny| x_range.clone().map(move |nx| (nx, ny))).filter(|&(nx, ny)| view.is_alive(nx, ny));
+        alive.count() as u8 - view.is_alive(x, y) as u8
+    };
+    let colors: Vec<[u8; 4]> = (0..view_width * view_height)
+        .map(|i| {
+            let (x, y) = (i % view_width, i / view_width);
+            let neighbors = if palette.coloring == Coloring::Neighbors { neighbors(x, y) } else { 0 };
+            let age = ages.as_ref().map(|ages| ages[i]);
+            palette.color(view.is_alive(x, y), view.fade(x, y), age, neighbors)
+        })
+        .collect();
+
+    for (i, pixel) in frame_buffer.chunks_exact_mut(4).enumerate() {
+        let (x, y) = camera.cell_at((i % width) as f64, (i / width) as f64);
+        let (x, y) = (((x - left) >> shift) as usize, ((y - top) >> shift) as usize);
+        pixel.copy_from_slice(&colors[y * view_width + x]);
+    }
+}
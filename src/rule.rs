@@ -0,0 +1,133 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A life-like cellular automaton rule in B/S notation, e.g. `B3/S23` for Conway's Life.
+///
+/// The rule is stored as two lookup tables indexed by the number of alive neighbors (0..=8).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`.
+    pub const CONWAY: Rule = Rule::new(&[3], &[2, 3]);
+
+    /// Builds a rule from the neighbor counts that cause a birth and those that let a cell survive.
+    /// Counts above 8 are ignored.
+    pub const fn new(birth: &[u8], survival: &[u8]) -> Self {
+        Rule {
+            birth: table(birth),
+            survival: table(survival),
+        }
+    }
+
+    pub fn birth(&self, neighbors: u8) -> bool {
+        self.birth[neighbors as usize]
+    }
+
+    pub fn survival(&self, neighbors: u8) -> bool {
+        self.survival[neighbors as usize]
+    }
+
+    /// Whether a cell is alive in the next generation given its current state and neighbor count.
+    pub fn next_state(&self, alive: bool, neighbors: u8) -> bool {
+        if alive {
+            self.survival[neighbors as usize]
+        } else {
+            self.birth[neighbors as usize]
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::CONWAY
+    }
+}
+
+const fn table(counts: &[u8]) -> [bool; 9] {
+    let mut table = [false; 9];
+    let mut i = 0;
+    while i < counts.len() {
+        if counts[i] <= 8 {
+            table[counts[i] as usize] = true;
+        }
+        i += 1;
+    }
+    table
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |table: &[bool; 9]| -> String {
+            (0..9).filter(|&n| table[n]).map(|n| char::from(b'0' + n as u8)).collect()
+        };
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseRuleError {
+    /// The rule does not consist of a birth and a survival part separated by `/`.
+    MissingSeparator,
+    /// A part is neither prefixed with `B`/`S` nor a plain list of digits.
+    InvalidPart(String),
+    /// A neighbor count is not a digit between 0 and 8.
+    InvalidCount(char),
+}
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRuleError::MissingSeparator => write!(f, "rule must have the form B<digits>/S<digits>"),
+            ParseRuleError::InvalidPart(part) => write!(f, "invalid rule part '{}'", part),
+            ParseRuleError::InvalidCount(c) => write!(f, "invalid neighbor count '{}', expected 0-8", c),
+        }
+    }
+}
+
+impl Error for ParseRuleError {}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    /// Parses `B3/S23`-style notation (case-insensitive, parts in either order) as well as the
+    /// legacy survival/birth form `23/3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, second) = s.trim().split_once('/').ok_or(ParseRuleError::MissingSeparator)?;
+        let counts = |digits: &str| -> Result<[bool; 9], ParseRuleError> {
+            let mut table = [false; 9];
+            for c in digits.chars() {
+                match c.to_digit(10) {
+                    Some(n) if n <= 8 => table[n as usize] = true,
+                    _ => return Err(ParseRuleError::InvalidCount(c)),
+                }
+            }
+            Ok(table)
+        };
+
+        let mut birth = None;
+        let mut survival = None;
+        for part in [first, second] {
+            let mut chars = part.chars();
+            match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') if birth.is_none() => birth = Some(counts(chars.as_str())?),
+                Some('S') if survival.is_none() => survival = Some(counts(chars.as_str())?),
+                _ if part.chars().all(|c| c.is_ascii_digit()) => break,
+                _ => return Err(ParseRuleError::InvalidPart(part.to_string())),
+            }
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Rule { birth, survival }),
+            (None, None) => Ok(Rule {
+                birth: counts(second)?,
+                survival: counts(first)?,
+            }),
+            _ => Err(ParseRuleError::InvalidPart(s.to_string())),
+        }
+    }
+}
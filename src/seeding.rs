@@ -0,0 +1,217 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::grid::Grid;
+use crate::pattern::Pattern;
+
+/// How a fresh board is filled with random cells.
+///
+/// Every strategy draws from a ChaCha8 RNG, so the same seed always yields the same board.
+/// `density` is the probability of a cell being alive wherever a strategy fills at random.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub enum Seeding {
+    /// Uniform noise over the whole board.
+    #[default]
+    Uniform,
+    /// Uniform noise in a `width` x `height` box centered on an otherwise empty board.
+    Box { width: usize, height: usize },
+    /// A centered `size` x `size` box of noise that is invariant under the given symmetry, as
+    /// used by apgsearch to find symmetric objects.
+    Symmetric { symmetry: Symmetry, size: usize },
+    /// Noise whose local density follows a Perlin gradient noise field with features about
+    /// `scale` cells across, averaging `density` over the board.
+    Perlin { scale: usize },
+    /// `count` copies of the given patterns, each picked at random and stamped at a random
+    /// position. `density` is not used.
+    Stamp { patterns: Vec<Pattern>, count: usize },
+}
+
+/// Symmetry groups for symmetric soups, named as in apgsearch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// No symmetry.
+    C1,
+    /// Invariant under rotation by 180 degrees.
+    C2,
+    /// Invariant under rotation by 90 degrees.
+    C4,
+    /// Mirrored across the vertical axis.
+    D2,
+    /// Mirrored across both axes.
+    D4,
+    /// Mirrored across both axes and both diagonals.
+    D8,
+}
+
+impl Symmetry {
+    /// All images of `(x, y)` in a `size` x `size` box under the symmetry group.
+    fn orbit(&self, x: usize, y: usize, size: usize) -> Vec<(usize, usize)> {
+        let m = size - 1;
+        let rotations = [(x, y), (m - y, x), (m - x, m - y), (y, m - x)];
+        let mirrors = [(m - x, y), (x, m - y), (y, x), (m - y, m - x)];
+        match self {
+            Symmetry::C1 => vec![(x, y)],
+            Symmetry::C2 => vec![rotations[0], rotations[2]],
+            Symmetry::C4 => rotations.to_vec(),
+            Symmetry::D2 => vec![(x, y), mirrors[0]],
+            Symmetry::D4 => vec![rotations[0], rotations[2], mirrors[0], mirrors[1]],
+            Symmetry::D8 => rotations.iter().chain(mirrors.iter()).copied().collect(),
+        }
+    }
+}
+
+/// Fills `cells` according to `seeding`, replacing its previous contents.
+pub(crate) fn fill(cells: &mut Grid, seeding: &Seeding, rng_seed: u64, density: f64) {
+    let mut rng = ChaCha8Rng::seed_from_u64(rng_seed);
+    let density = density.clamp(0.0, 1.0);
+    let (width, height) = (cells.width(), cells.height());
+    cells.cells_mut().fill((0, 0));
+
+    match seeding {
+        Seeding::Uniform => fill_box(cells, &mut rng, 0, 0, width, height, density),
+        Seeding::Box { width: box_width, height: box_height } => {
+            let (box_width, box_height) = ((*box_width).min(width), (*box_height).min(height));
+            fill_box(cells, &mut rng, (width - box_width) / 2, (height - box_height) / 2, box_width, box_height, density);
+        }
+        Seeding::Symmetric { symmetry, size } => {
+            let size = (*size).min(width).min(height);
+            let (left, top) = ((width - size) / 2, (height - size) / 2);
+            let noise: Vec<bool> = (0..size * size).map(|_| rng.gen_bool(density)).collect();
+            for y in 0..size {
+                for x in 0..size {
+                    // every cell copies the noise of the first cell of its orbit
+                    let (ox, oy) = symmetry.orbit(x, y, size).into_iter().min_by_key(|&(x, y)| (y, x)).unwrap();
+                    if noise[oy * size + ox] {
+                        cells.set(left + x, top + y, (1, 0xff));
+                    }
+                }
+            }
+        }
+        Seeding::Perlin { scale } => {
+            let noise = Perlin::new(&mut rng);
+            let scale = (*scale).max(1) as f64;
+            for y in 0..height {
+                for x in 0..width {
+                    let local = density * (1.0 + noise.sample(x as f64 / scale, y as f64 / scale));
+                    if rng.gen_bool(local.clamp(0.0, 1.0)) {
+                        cells.set(x, y, (1, 0xff));
+                    }
+                }
+            }
+        }
+        Seeding::Stamp { patterns, count } => {
+            if patterns.is_empty() {
+                return;
+            }
+            for _ in 0..*count {
+                let pattern = &patterns[rng.gen_range(0..patterns.len())];
+                let (left, top) = (rng.gen_range(0..width), rng.gen_range(0..height));
+                for &(x, y) in pattern.cells() {
+                    if left + x < width && top + y < height {
+                        cells.set(left + x, top + y, (1, 0xff));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn fill_box(cells: &mut Grid, rng: &mut ChaCha8Rng, left: usize, top: usize, width: usize, height: usize, density: f64) {
+    for y in top..top + height {
+        for x in left..left + width {
+            if rng.gen_bool(density) {
+                cells.set(x, y, (1, 0xff));
+            }
+        }
+    }
+}
+
+/// Classic 2D Perlin gradient noise over a 256-cell repeating lattice, roughly in `-1.0..=1.0`.
+struct Perlin {
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    fn new(rng: &mut ChaCha8Rng) -> Self {
+        let mut table: Vec<u8> = (0..=255).collect();
+        for i in (1..table.len()).rev() {
+            table.swap(i, rng.gen_range(0..=i));
+        }
+        let mut permutation = [0; 512];
+        for (i, entry) in permutation.iter_mut().enumerate() {
+            *entry = table[i % 256];
+        }
+        Perlin { permutation }
+    }
+
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        let (cell_x, cell_y) = ((x.floor() as i64 & 255) as usize, (y.floor() as i64 & 255) as usize);
+        let (x, y) = (x - x.floor(), y - y.floor());
+        let fade = |t: f64| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let lerp = |t: f64, a: f64, b: f64| a + t * (b - a);
+        let p = &self.permutation;
+        let hash = |cx: usize, cy: usize| p[p[cx] as usize + cy];
+        let gradient = |hash: u8, x: f64, y: f64| match hash & 3 {
+            0 => x + y,
+            1 => -x + y,
+            2 => x - y,
+            _ => -x - y,
+        };
+
+        let (u, v) = (fade(x), fade(y));
+        let top = lerp(u, gradient(hash(cell_x, cell_y), x, y), gradient(hash(cell_x + 1, cell_y), x - 1.0, y));
+        let bottom = lerp(
+            u,
+            gradient(hash(cell_x, cell_y + 1), x, y - 1.0),
+            gradient(hash(cell_x + 1, cell_y + 1), x - 1.0, y - 1.0),
+        );
+        lerp(v, top, bottom)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseSeedingError(String);
+
+impl fmt::Display for ParseSeedingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown seeding '{}', expected uniform, box:<w>x<h>, c1|c2|c4|d2|d4|d8:<size> or perlin:<scale>",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseSeedingError {}
+
+impl FromStr for Seeding {
+    type Err = ParseSeedingError;
+
+    /// Parses the command line form of every strategy except `Stamp`, which needs patterns.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseSeedingError(s.to_string());
+        let lower = s.to_ascii_lowercase();
+        let (kind, argument) = lower.split_once(':').unwrap_or((&lower, ""));
+        let number = |value: &str| value.parse::<usize>().ok().filter(|&n| n > 0).ok_or_else(error);
+        let symmetry = match kind {
+            "uniform" if argument.is_empty() => return Ok(Seeding::Uniform),
+            "box" => {
+                let (width, height) = argument.split_once('x').ok_or_else(error)?;
+                return Ok(Seeding::Box { width: number(width)?, height: number(height)? });
+            }
+            "perlin" => return Ok(Seeding::Perlin { scale: number(argument)? }),
+            "c1" => Symmetry::C1,
+            "c2" => Symmetry::C2,
+            "c4" => Symmetry::C4,
+            "d2" => Symmetry::D2,
+            "d4" => Symmetry::D4,
+            "d8" => Symmetry::D8,
+            _ => return Err(error()),
+        };
+        Ok(Seeding::Symmetric { symmetry, size: number(argument)? })
+    }
+}
@@ -0,0 +1,303 @@
+//! Versioned binary snapshots of a whole session, so long runs can be resumed exactly.
+//!
+//! All integers are little-endian. Version 1 is laid out as:
+//!
+//! ```text
+//! magic        8 bytes  "GOLSNAP\0"
+//! version      u16
+//! width        u64
+//! height       u64
+//! generation   u64
+//! rng seed     u8 (0 = none, 1 = present) followed by u64
+//! rule         u16 length + UTF-8, e.g. "B3/S23"
+//! topology     u16 length + UTF-8, e.g. "torus"
+//! engine       u16 length + UTF-8, e.g. "dense"
+//! step log     u8
+//! viewport     i64 x, i64 y
+//! fade         width * height bytes, row by row
+//! alive        width * height bits, row by row, least significant bit first
+//! plane cells  u64 count + count * (i64 x, i64 y); only used by the unbounded engines
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::grid::Grid;
+use crate::rule::Rule;
+use crate::topology::Topology;
+use crate::universe::{Engine, Universe};
+
+const MAGIC: &[u8; 8] = b"GOLSNAP\0";
+const VERSION: u16 = 1;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    /// The data does not start with the snapshot magic bytes.
+    NotASnapshot,
+    /// The snapshot was written by a newer, unknown format version.
+    UnsupportedVersion(u16),
+    /// A field holds a value that cannot be restored.
+    Malformed(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(error) => write!(f, "could not access snapshot: {}", error),
+            SnapshotError::NotASnapshot => write!(f, "not a snapshot file"),
+            SnapshotError::UnsupportedVersion(version) => write!(f, "unsupported snapshot version {}", version),
+            SnapshotError::Malformed(message) => write!(f, "malformed snapshot: {}", message),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        SnapshotError::Io(error)
+    }
+}
+
+impl Universe {
+    /// Writes a snapshot of the universe to a file.
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_snapshot(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Restores a universe from a snapshot file.
+    pub fn load_snapshot(path: impl AsRef<Path>) -> Result<Universe, SnapshotError> {
+        Universe::read_snapshot(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Serializes the board, fade trails, generation, RNG seed, rule, topology and engine.
+    /// The thread count is not part of a snapshot.
+    pub fn write_snapshot(&self, writer: &mut impl Write) -> Result<(), SnapshotError> {
+        let grid = self.to_grid();
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(grid.width() as u64).to_le_bytes())?;
+        writer.write_all(&(grid.height() as u64).to_le_bytes())?;
+        writer.write_all(&(self.generation() as u64).to_le_bytes())?;
+        match self.rng_seed() {
+            Some(seed) => {
+                writer.write_all(&[1])?;
+                writer.write_all(&seed.to_le_bytes())?;
+            }
+            None => writer.write_all(&[0; 9])?,
+        }
+        write_string(writer, &self.rule().to_string())?;
+        write_string(writer, &self.topology().to_string())?;
+        write_string(writer, &self.engine().to_string())?;
+        writer.write_all(&[self.step_log()])?;
+        writer.write_all(&self.viewport().0.to_le_bytes())?;
+        writer.write_all(&self.viewport().1.to_le_bytes())?;
+
+        let fades: Vec<u8> = grid.cells().iter().map(|cell| cell.1).collect();
+        writer.write_all(&fades)?;
+        let mut alive = vec![0u8; grid.cells().len().div_ceil(8)];
+        for (i, cell) in grid.cells().iter().enumerate() {
+            alive[i / 8] |= cell.0 << (i % 8);
+        }
+        writer.write_all(&alive)?;
+
+        let plane = self.plane_cells().unwrap_or_default();
+        writer.write_all(&(plane.len() as u64).to_le_bytes())?;
+        for (x, y) in plane {
+            writer.write_all(&x.to_le_bytes())?;
+            writer.write_all(&y.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Deserializes a universe written by [`Universe::write_snapshot`]. The restored universe
+    /// steps on a single thread.
+    pub fn read_snapshot(reader: &mut impl Read) -> Result<Universe, SnapshotError> {
+        Universe::read_snapshot_fields(reader).map_err(|error| match error {
+            SnapshotError::Io(error) if error.kind() == io::ErrorKind::UnexpectedEof => SnapshotError::Malformed("the file ends early".to_string()),
+            error => error,
+        })
+    }
+
+    fn read_snapshot_fields(reader: &mut impl Read) -> Result<Universe, SnapshotError> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        let version = u16::from_le_bytes(read_array(reader)?);
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let width = read_size(reader, "width")?;
+        let height = read_size(reader, "height")?;
+        if width == 0 || height == 0 || width.checked_mul(height).is_none() {
+            return Err(SnapshotError::Malformed(format!("invalid board size {}x{}", width, height)));
+        }
+        let generation = read_size(reader, "generation")?;
+        let [has_seed] = read_array(reader)?;
+        let seed = u64::from_le_bytes(read_array(reader)?);
+        let rng_seed = (has_seed == 1).then_some(seed);
+        let rule: Rule = read_string(reader)?.parse().map_err(|error| SnapshotError::Malformed(format!("rule: {}", error)))?;
+        let topology: Topology = read_string(reader)?.parse().map_err(|error| SnapshotError::Malformed(format!("topology: {}", error)))?;
+        let engine: Engine = read_string(reader)?.parse().map_err(|error| SnapshotError::Malformed(format!("engine: {}", error)))?;
+        let [step_log] = read_array(reader)?;
+        let viewport = (i64::from_le_bytes(read_array(reader)?), i64::from_le_bytes(read_array(reader)?));
+
+        // read in pieces rather than allocated up front, so a corrupt size cannot exhaust memory
+        let fades = read_bytes(reader, width * height, "fade")?;
+        let alive = read_bytes(reader, (width * height).div_ceil(8), "alive")?;
+        let plane_count = u64::from_le_bytes(read_array(reader)?);
+        let mut plane = Vec::new();
+        for _ in 0..plane_count {
+            plane.push((i64::from_le_bytes(read_array(reader)?), i64::from_le_bytes(read_array(reader)?)));
+        }
+
+        let mut grid = Grid::new(width, height);
+        for (i, cell) in grid.cells_mut().iter_mut().enumerate() {
+            *cell = (alive[i / 8] >> (i % 8) & 1, fades[i]);
+        }
+        let unbounded = matches!(engine, Engine::HashLife | Engine::Sparse);
+        if unbounded && rule.birth(0) {
+            return Err(SnapshotError::Malformed(format!("engine {} does not support rule {}", engine, rule)));
+        }
+
+        let mut universe = if unbounded {
+            let mut universe = Universe::new(width, height).with_rule(rule).with_step_log(step_log).with_engine(engine);
+            for (x, y) in plane {
+                universe.set_plane_alive(x, y, true);
+            }
+            universe.set_viewport(viewport.0, viewport.1);
+            universe
+        } else {
+            Universe::from_grid(grid).with_rule(rule).with_step_log(step_log).with_engine(engine)
+        };
+        universe.set_topology(topology);
+        universe.set_generation(generation);
+        universe.set_rng_seed(rng_seed);
+        Ok(universe)
+    }
+}
+
+fn write_string(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    writer.write_all(&(value.len() as u16).to_le_bytes())?;
+    writer.write_all(value.as_bytes())
+}
+
+/// Reads exactly `length` bytes of the `what` section, growing the buffer only as data arrives.
+fn read_bytes(reader: &mut impl Read, length: usize, what: &str) -> Result<Vec<u8>, SnapshotError> {
+    let mut bytes = Vec::new();
+    reader.take(length as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < length {
+        return Err(SnapshotError::Malformed(format!("the file ends inside the {} cells", what)));
+    }
+    Ok(bytes)
+}
+
+fn read_string(reader: &mut impl Read) -> Result<String, SnapshotError> {
+    let length = u16::from_le_bytes(read_array(reader)?);
+    let mut bytes = vec![0; length as usize];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| SnapshotError::Malformed("string is not UTF-8".to_string()))
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_size(reader: &mut impl Read, field: &str) -> Result<usize, SnapshotError> {
+    let value = u64::from_le_bytes(read_array(reader)?);
+    usize::try_from(value).map_err(|_| SnapshotError::Malformed(format!("{} {} is too large", field, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotError;
+    use crate::topology::Topology;
+    use crate::universe::{Engine, Universe};
+
+    fn snapshot(universe: &Universe) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        universe.write_snapshot(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Everything a snapshot is meant to restore.
+    fn state(universe: &Universe) -> impl PartialEq + std::fmt::Debug {
+        let cells: Vec<(bool, u8)> = (0..universe.height())
+            .flat_map(|y| (0..universe.width()).map(move |x| (universe.is_alive(x, y), universe.fade(x, y))))
+            .collect();
+        let mut plane = universe.plane_cells();
+        if let Some(plane) = &mut plane {
+            plane.sort_unstable();
+        }
+        (
+            (universe.width(), universe.height(), universe.generation(), universe.rng_seed()),
+            (universe.rule(), universe.topology(), universe.engine(), universe.step_log(), universe.viewport()),
+            cells,
+            plane,
+        )
+    }
+
+    #[test]
+    fn round_trips_every_engine() {
+        for engine in [Engine::Dense, Engine::Packed, Engine::HashLife, Engine::Sparse] {
+            let mut universe = Universe::new(37, 21)
+                .with_rule("B36/S23".parse().unwrap())
+                .with_topology(Topology::Klein)
+                .with_step_log(3)
+                .with_engine(engine);
+            universe.seed_with(7, 0.3);
+            for _ in 0..5 {
+                universe.step().unwrap();
+            }
+            // cells beyond the board only survive on the unbounded engines
+            universe.set_alive_at(-40, 90, true);
+            universe.set_viewport(3, -2);
+            let restored = Universe::read_snapshot(&mut snapshot(&universe).as_slice()).unwrap();
+            assert_eq!(state(&restored), state(&universe), "{}", engine);
+        }
+    }
+
+    #[test]
+    fn rejects_foreign_and_damaged_files() {
+        let mut universe = Universe::new(9, 5).with_engine(Engine::Sparse);
+        universe.seed_with(1, 0.5);
+        let bytes = snapshot(&universe);
+
+        let read = |bytes: &[u8]| Universe::read_snapshot(&mut &bytes[..]);
+        assert!(matches!(read(b"GIF89a, not a snapshot"), Err(SnapshotError::NotASnapshot)));
+        let mut newer = bytes.clone();
+        newer[8] = 2;
+        assert!(matches!(read(&newer), Err(SnapshotError::UnsupportedVersion(2))));
+        for length in 0..bytes.len() {
+            assert!(matches!(read(&bytes[..length]), Err(SnapshotError::Malformed(_))), "truncated to {} bytes", length);
+        }
+
+        // a header claiming a huge board must not allocate it
+        let mut huge = bytes.clone();
+        huge[10..18].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        huge[18..26].copy_from_slice(&(1u64 << 20).to_le_bytes());
+        assert!(matches!(read(&huge), Err(SnapshotError::Malformed(_))));
+        let mut empty = bytes.clone();
+        empty[10..18].copy_from_slice(&0u64.to_le_bytes());
+        assert!(matches!(read(&empty), Err(SnapshotError::Malformed(_))));
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::grid::Grid;
+use crate::rule::Rule;
+use crate::stats::Changes;
+
+/// An unbounded universe storing only its alive cells, addressed by `i64` coordinates.
+///
+/// Unlike the toroidal board there are no edges: patterns grow freely and gliders travel on
+/// forever. Memory and step time scale with the population rather than with the area. Rules
+/// with `B0` would fill the infinite plane and are not supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseUniverse {
+    rule: Rule,
+    alive: HashSet<(i64, i64)>,
+}
+
+impl SparseUniverse {
+    /// Creates an empty universe. Panics if the rule births cells with zero neighbors.
+    pub fn new(rule: Rule) -> Self {
+        assert!(!rule.birth(0), "sparse universes do not support B0 rules");
+        SparseUniverse {
+            rule,
+            alive: HashSet::new(),
+        }
+    }
+
+    /// Imports the alive cells of a dense grid, with the grid's top-left cell at `(0, 0)`.
+    pub fn from_grid(grid: &Grid, rule: Rule) -> Self {
+        let mut universe = SparseUniverse::new(rule);
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if grid.is_alive(x, y) {
+                    universe.alive.insert((x as i64, y as i64));
+                }
+            }
+        }
+        universe
+    }
+
+    /// Copies the cells covered by `grid`, with its top-left cell at `offset`, into it. Fade
+    /// values are reset.
+    pub fn export(&self, grid: &mut Grid, offset: (i64, i64)) {
+        self.export_scaled(grid, offset, 0);
+    }
+
+    /// Like [`SparseUniverse::export`], but every cell of `grid` stands for a `2^shift` x
+    /// `2^shift` square of the plane and is alive if anything in that square is.
+    pub fn export_scaled(&self, grid: &mut Grid, offset: (i64, i64), shift: u32) {
+        for cell in grid.cells_mut() {
+            *cell = (0, 0);
+        }
+        let (width, height) = (grid.width() as i64, grid.height() as i64);
+        for &(x, y) in &self.alive {
+            let (x, y) = ((x - offset.0) >> shift, (y - offset.1) >> shift);
+            if (0..width).contains(&x) && (0..height).contains(&y) {
+                grid.set(x as usize, y as usize, (1, 0xff));
+            }
+        }
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Changes the rule. Panics on `B0` rules.
+    pub fn set_rule(&mut self, rule: Rule) {
+        assert!(!rule.birth(0), "sparse universes do not support B0 rules");
+        self.rule = rule;
+    }
+
+    pub fn is_alive(&self, x: i64, y: i64) -> bool {
+        self.alive.contains(&(x, y))
+    }
+
+    pub fn set_alive(&mut self, x: i64, y: i64, alive: bool) {
+        if alive {
+            self.alive.insert((x, y));
+        } else {
+            self.alive.remove(&(x, y));
+        }
+    }
+
+    pub fn population(&self) -> usize {
+        self.alive.len()
+    }
+
+    /// Iterates over the coordinates of all alive cells, in no particular order.
+    pub fn cells(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.alive.iter().copied()
+    }
+
+    /// The smallest `(min_x, min_y, max_x, max_y)` rectangle containing all alive cells, or
+    /// `None` if the universe is empty.
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        self.alive.iter().fold(None, |bounds, &(x, y)| match bounds {
+            None => Some((x, y, x, y)),
+            Some((min_x, min_y, max_x, max_y)) => Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))),
+        })
+    }
+
+    /// Advances the universe by one generation. Returns the cells that were born and died.
+    pub fn step(&mut self) -> Changes {
+        let mut neighbors: HashMap<(i64, i64), u8> = HashMap::with_capacity(self.alive.len() * 8);
+        for &(x, y) in &self.alive {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy) != (0, 0) {
+                        *neighbors.entry((x + dx, y + dy)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let mut next: HashSet<(i64, i64)> = neighbors
+            .iter()
+            .filter(|&(cell, &count)| self.rule.next_state(self.alive.contains(cell), count))
+            .map(|(&cell, _)| cell)
+            .collect();
+        if self.rule.survival(0) {
+            // isolated cells never show up in the neighbor counts
+            next.extend(self.alive.iter().filter(|cell| !neighbors.contains_key(cell)));
+        }
+
+        let births = next.difference(&self.alive).count();
+        let changes = Changes {
+            births,
+            deaths: self.alive.len() + births - next.len(),
+        };
+        self.alive = next;
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseUniverse;
+    use crate::grid::Grid;
+    use crate::hashlife::HashLife;
+    use crate::rule::Rule;
+    use crate::seeding::{self, Seeding};
+
+    const RULES: [&str; 5] = ["B3/S23", "B36/S23", "B36/S125", "B3678/S34678", "B34/S34"];
+
+    fn sorted(mut cells: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+        cells.sort_unstable();
+        cells
+    }
+
+    #[test]
+    fn sparse_matches_hashlife() {
+        for step_log in [0, 4] {
+            for (seed, rule) in RULES.iter().enumerate() {
+                let rule: Rule = rule.parse().unwrap();
+                let mut soup = Grid::new(24, 24);
+                seeding::fill(&mut soup, &Seeding::Uniform, seed as u64, 0.35);
+                let mut sparse = SparseUniverse::from_grid(&soup, rule);
+                let mut life = HashLife::from_grid(&soup, rule);
+                life.set_step_log(step_log);
+                // a glider heading up and left, so the plane also grows into negative coordinates
+                for (x, y) in [(-10, -10), (-9, -10), (-8, -10), (-10, -9), (-9, -8)] {
+                    sparse.set_alive(x, y, true);
+                    life.set_alive(x, y, true);
+                }
+
+                for step in 1..=64 >> step_log {
+                    life.step().unwrap();
+                    for _ in 0..1 << step_log {
+                        sparse.step();
+                    }
+                    let case = format!("{} at step_log {}, step {}", rule, step_log, step);
+                    assert_eq!(life.generation(), step << step_log, "{}", case);
+                    assert_eq!(sparse.population() as u64, life.population(), "{}", case);
+                    assert_eq!(sparse.bounding_box(), life.bounding_box(), "{}", case);
+                    assert_eq!(sorted(sparse.cells().collect()), sorted(life.cells()), "{}", case);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Per-generation statistics of a running universe, and their export for plotting.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::Add;
+use std::path::Path;
+
+use crate::universe::Universe;
+
+/// Cells that changed in one step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+    pub births: usize,
+    pub deaths: usize,
+}
+
+impl Changes {
+    pub fn any(&self) -> bool {
+        self.births > 0 || self.deaths > 0
+    }
+}
+
+impl Add for Changes {
+    type Output = Changes;
+
+    fn add(self, other: Changes) -> Changes {
+        Changes {
+            births: self.births + other.births,
+            deaths: self.deaths + other.deaths,
+        }
+    }
+}
+
+/// Measurements of a universe at one generation, see [`Universe::stats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub generation: usize,
+    pub population: usize,
+    /// What the step leading to this generation changed. `None` before the first step after
+    /// seeding, and on the hashlife engine, which does not visit the generations it jumps over.
+    pub changes: Option<Changes>,
+    /// Smallest rectangle holding all alive cells, as `(min_x, min_y, max_x, max_y)` in board
+    /// coordinates, or `None` if every cell is dead.
+    pub bounds: Option<(i64, i64, i64, i64)>,
+}
+
+impl Stats {
+    /// Number of cells that were born or died in the last step.
+    pub fn activity(&self) -> Option<usize> {
+        self.changes.map(|changes| changes.births + changes.deaths)
+    }
+}
+
+/// The statistics of the latest generations, oldest first, up to a fixed number of them.
+#[derive(Clone, Debug)]
+pub struct History {
+    capacity: usize,
+    samples: VecDeque<Stats>,
+}
+
+impl History {
+    /// Creates an empty history keeping the latest `capacity` generations.
+    pub fn new(capacity: usize) -> Self {
+        History {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Appends a sample, forgetting the oldest one if the history is full.
+    pub fn push(&mut self, stats: Stats) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(stats);
+    }
+
+    /// Appends the current statistics of `universe`.
+    pub fn record(&mut self, universe: &Universe) {
+        self.push(universe.stats());
+    }
+
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = &Stats> + ExactSizeIterator {
+        self.samples.iter()
+    }
+
+    pub fn latest(&self) -> Option<&Stats> {
+        self.samples.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// Layout of an exported time series.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsFormat {
+    /// One line per generation under a header, unknown values left empty.
+    Csv,
+    /// An array with one object per generation, unknown values `null`.
+    Json,
+}
+
+impl StatsFormat {
+    /// Guesses the format from a file extension: JSON for `.json`, CSV otherwise.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => StatsFormat::Json,
+            _ => StatsFormat::Csv,
+        }
+    }
+}
+
+/// Streams statistics to a writer one generation at a time, so long runs need no memory.
+///
+/// Call [`StatsWriter::finish`] at the end, which completes a JSON array and flushes.
+pub struct StatsWriter<W: Write> {
+    writer: W,
+    format: StatsFormat,
+    written: usize,
+}
+
+impl StatsWriter<BufWriter<File>> {
+    /// Creates the file at `path`, choosing the format from its extension.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let format = StatsFormat::from_path(&path);
+        StatsWriter::new(BufWriter::new(File::create(path)?), format)
+    }
+}
+
+impl<W: Write> StatsWriter<W> {
+    /// Starts a time series in `writer`, writing the CSV header or the opening bracket.
+    pub fn new(mut writer: W, format: StatsFormat) -> io::Result<Self> {
+        match format {
+            StatsFormat::Csv => writeln!(writer, "generation,population,births,deaths,activity,min_x,min_y,max_x,max_y")?,
+            StatsFormat::Json => write!(writer, "[")?,
+        }
+        Ok(StatsWriter { writer, format, written: 0 })
+    }
+
+    pub fn write(&mut self, stats: &Stats) -> io::Result<()> {
+        let births = stats.changes.map(|changes| changes.births);
+        let deaths = stats.changes.map(|changes| changes.deaths);
+        let bounds = stats.bounds.map(|(min_x, min_y, max_x, max_y)| [min_x, min_y, max_x, max_y]);
+        match self.format {
+            StatsFormat::Csv => {
+                let field = |value: Option<String>| value.unwrap_or_default();
+                let bounds = bounds.map_or_else(|| ",,,".to_string(), |bounds| bounds.map(|value| value.to_string()).join(","));
+                writeln!(
+                    self.writer,
+                    "{},{},{},{},{},{}",
+                    stats.generation,
+                    stats.population,
+                    field(births.map(|births| births.to_string())),
+                    field(deaths.map(|deaths| deaths.to_string())),
+                    field(stats.activity().map(|activity| activity.to_string())),
+                    bounds
+                )?;
+            }
+            StatsFormat::Json => {
+                let field = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+                write!(
+                    self.writer,
+                    "{}\n  {{\"generation\": {}, \"population\": {}, \"births\": {}, \"deaths\": {}, \"activity\": {}, \"bounds\": {}}}",
+                    if self.written == 0 { "" } else { "," },
+                    stats.generation,
+                    stats.population,
+                    field(births.map(|births| births.to_string())),
+                    field(deaths.map(|deaths| deaths.to_string())),
+                    field(stats.activity().map(|activity| activity.to_string())),
+                    field(bounds.map(|bounds| format!("{:?}", bounds)))
+                )?;
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Number of generations written so far.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Ends the time series and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.format == StatsFormat::Json {
+            writeln!(self.writer, "{}]", if self.written == 0 { "" } else { "\n" })?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
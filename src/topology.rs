@@ -0,0 +1,107 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the edges of a bounded board are glued together, i.e. what lies beyond them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Topology {
+    /// Opposite edges are joined: leaving on the right re-enters on the left, leaving at the
+    /// bottom re-enters at the top.
+    #[default]
+    Torus,
+    /// Everything beyond the edges is permanently dead.
+    Dead,
+    /// The edges act as mirrors: the cells beyond an edge copy the cells just inside it.
+    Reflect,
+    /// Left and right are joined as on a torus; top and bottom are joined with a flip, so
+    /// leaving at the bottom near the left re-enters at the top near the right.
+    Klein,
+    /// The real projective plane (cross-surface): both pairs of edges are joined with a flip.
+    Projective,
+}
+
+impl Topology {
+    /// Maps a cell position that may lie beyond the edges of a `width` x `height` board onto the
+    /// board. Returns `None` if the position is outside and the topology has no cell there.
+    pub fn resolve(&self, x: isize, y: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (w, h) = (width as isize, height as isize);
+        let inside = |index: isize, limit: isize| (0..limit).contains(&index);
+        let (x, y) = match self {
+            Topology::Torus => (x.rem_euclid(w), y.rem_euclid(h)),
+            Topology::Dead if inside(x, w) && inside(y, h) => (x, y),
+            Topology::Dead => return None,
+            Topology::Reflect => (mirror(x, w), mirror(y, h)),
+            Topology::Klein => {
+                let x = x.rem_euclid(w);
+                if inside(y, h) {
+                    (x, y)
+                } else {
+                    (w - 1 - x, y.rem_euclid(h))
+                }
+            }
+            Topology::Projective => {
+                let (mut x, mut y) = (x, y);
+                if !inside(x, w) {
+                    x = x.rem_euclid(w);
+                    y = h - 1 - y;
+                }
+                if !inside(y, h) {
+                    y = y.rem_euclid(h);
+                    x = w - 1 - x;
+                }
+                (x, y)
+            }
+        };
+        Some((x as usize, y as usize))
+    }
+}
+
+/// Folds `index` back into `0..limit` as if the edges were mirrors: -1 maps to 0, `limit` to
+/// `limit - 1`.
+fn mirror(index: isize, limit: isize) -> isize {
+    let folded = index.rem_euclid(2 * limit);
+    if folded < limit {
+        folded
+    } else {
+        2 * limit - 1 - folded
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Topology::Torus => "torus",
+            Topology::Dead => "dead",
+            Topology::Reflect => "reflect",
+            Topology::Klein => "klein",
+            Topology::Projective => "projective",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTopologyError(String);
+
+impl fmt::Display for ParseTopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown topology '{}', expected torus, dead, reflect, klein or projective", self.0)
+    }
+}
+
+impl Error for ParseTopologyError {}
+
+impl FromStr for Topology {
+    type Err = ParseTopologyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "torus" => Ok(Topology::Torus),
+            "dead" => Ok(Topology::Dead),
+            "reflect" => Ok(Topology::Reflect),
+            "klein" => Ok(Topology::Klein),
+            "projective" | "cross-surface" => Ok(Topology::Projective),
+            _ => Err(ParseTopologyError(s.to_string())),
+        }
+    }
+}
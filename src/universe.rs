@@ -0,0 +1,716 @@
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::bitgrid::BitGrid;
+use crate::grid::Grid;
+use crate::hashlife::{HashLife, PlaneOverflow, MAX_STEP_LOG};
+use crate::pattern::Pattern;
+use crate::parallel::step_in_bands;
+use crate::rule::Rule;
+use crate::seeding::{self, Seeding};
+use crate::sparse::SparseUniverse;
+use crate::stats::{Changes, Stats};
+use crate::topology::Topology;
+
+/// Default board dimensions used by the windowed binary.
+pub const WIDTH: usize = 640;
+pub const HEIGHT: usize = 480;
+
+/// Probability of a cell starting alive in a random soup.
+pub const DEFAULT_DENSITY: f64 = 0.5;
+
+/// The simulation backend a universe is stepped with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Engine {
+    /// One `(alive, fade)` tuple per cell, keeping the fade trail of dead cells.
+    Dense,
+    /// One bit per cell in double-buffered `u64` words. Much faster, but without fade trails.
+    Packed,
+    /// A Hashlife quadtree on an unbounded plane, jumping `2^step_log` generations per step.
+    /// The board only serves as the visible window onto the plane.
+    HashLife,
+    /// A hash set of alive cells on an unbounded plane. The board only serves as the visible
+    /// window onto the plane.
+    Sparse,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseEngineError(String);
+
+impl fmt::Display for ParseEngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown engine '{}', expected dense, packed, hashlife or sparse", self.0)
+    }
+}
+
+impl Error for ParseEngineError {}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Engine::Dense => "dense",
+            Engine::Packed => "packed",
+            Engine::HashLife => "hashlife",
+            Engine::Sparse => "sparse",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Engine {
+    type Err = ParseEngineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dense" => Ok(Engine::Dense),
+            "packed" => Ok(Engine::Packed),
+            "hashlife" => Ok(Engine::HashLife),
+            "sparse" => Ok(Engine::Sparse),
+            _ => Err(ParseEngineError(s.to_string())),
+        }
+    }
+}
+
+enum Backend {
+    Dense(Grid),
+    Packed(BitGrid),
+    HashLife { life: HashLife, width: usize, height: usize },
+    Sparse { life: SparseUniverse, width: usize, height: usize },
+}
+
+/// A life-like board together with its rule and generation counter.
+///
+/// The dense and packed engines simulate a board of exactly this size whose edges are joined
+/// according to the topology. The hashlife and sparse engines simulate an unbounded plane, and
+/// the board is a window onto it whose top-left corner is the viewport position.
+pub struct Universe {
+    backend: Backend,
+    rule: Rule,
+    topology: Topology,
+    threads: usize,
+    step_log: u8,
+    viewport: (i64, i64),
+    rng_seed: Option<u64>,
+    generation: usize,
+    /// What the last step changed, when the engine knows.
+    changes: Option<Changes>,
+    /// Age of every board cell, row by row, while ages are tracked.
+    ages: Option<Vec<i16>>,
+}
+
+impl Universe {
+    /// Creates an empty universe of `width` x `height` dead cells.
+    pub fn new(width: usize, height: usize) -> Self {
+        Universe::from_grid(Grid::new(width, height))
+    }
+
+    /// Wraps an existing grid, e.g. one built from a loaded pattern, at generation 0 under
+    /// Conway's rule on the dense engine.
+    pub fn from_grid(cells: Grid) -> Self {
+        Universe {
+            backend: Backend::Dense(cells),
+            rule: Rule::CONWAY,
+            topology: Topology::Torus,
+            threads: 1,
+            step_log: 0,
+            viewport: (0, 0),
+            rng_seed: None,
+            generation: 0,
+            changes: None,
+            ages: None,
+        }
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.set_engine(engine);
+        self
+    }
+
+    /// Steps the board on `threads` worker threads, each handling a horizontal band of rows.
+    /// Results are identical to single-threaded stepping.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.set_threads(threads);
+        self
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        match &mut self.backend {
+            Backend::HashLife { life, .. } => life.set_rule(rule),
+            Backend::Sparse { life, .. } => life.set_rule(rule),
+            _ => {}
+        }
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Changes how the board edges are joined. Ignored by the unbounded engines.
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    /// Makes the hashlife engine advance `2^step_log` generations per step. The other engines
+    /// always advance a single generation.
+    pub fn with_step_log(mut self, step_log: u8) -> Self {
+        self.set_step_log(step_log);
+        self
+    }
+
+    pub fn step_log(&self) -> u8 {
+        self.step_log
+    }
+
+    pub fn set_step_log(&mut self, step_log: u8) {
+        self.step_log = step_log.min(MAX_STEP_LOG);
+        if let Backend::HashLife { life, .. } = &mut self.backend {
+            life.set_step_log(self.step_log);
+        }
+    }
+
+    /// Plane coordinates of the board's top-left cell on the unbounded engines.
+    pub fn viewport(&self) -> (i64, i64) {
+        self.viewport
+    }
+
+    /// Moves the window onto the plane. Has no effect on the dense and packed engines, whose
+    /// board is the whole universe.
+    pub fn set_viewport(&mut self, x: i64, y: i64) {
+        if matches!(self.backend, Backend::HashLife { .. } | Backend::Sparse { .. }) {
+            self.viewport = (x, y);
+            self.reset_ages();
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn engine(&self) -> Engine {
+        match self.backend {
+            Backend::Dense(_) => Engine::Dense,
+            Backend::Packed(_) => Engine::Packed,
+            Backend::HashLife { .. } => Engine::HashLife,
+            Backend::Sparse { .. } => Engine::Sparse,
+        }
+    }
+
+    /// Switches the backend, converting the current state. Fade trails are lost when leaving the
+    /// dense engine, and cells outside the board are lost when leaving an unbounded engine.
+    ///
+    /// Panics when switching to hashlife or sparse under a `B0` rule.
+    pub fn set_engine(&mut self, engine: Engine) {
+        if engine == self.engine() {
+            return;
+        }
+        let cells = self.to_grid();
+        self.viewport = (0, 0);
+        self.backend = match engine {
+            Engine::Dense => Backend::Dense(cells),
+            Engine::Packed => Backend::Packed(BitGrid::from_grid(&cells)),
+            Engine::HashLife => {
+                let mut life = HashLife::from_grid(&cells, self.rule);
+                life.set_step_log(self.step_log);
+                Backend::HashLife { life, width: cells.width(), height: cells.height() }
+            }
+            Engine::Sparse => Backend::Sparse {
+                life: SparseUniverse::from_grid(&cells, self.rule),
+                width: cells.width(),
+                height: cells.height(),
+            },
+        };
+        self.reset_ages();
+    }
+
+    pub fn width(&self) -> usize {
+        match &self.backend {
+            Backend::Dense(cells) => cells.width(),
+            Backend::Packed(bits) => bits.width(),
+            Backend::HashLife { width, .. } | Backend::Sparse { width, .. } => *width,
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        match &self.backend {
+            Backend::Dense(cells) => cells.height(),
+            Backend::Packed(bits) => bits.height(),
+            Backend::HashLife { height, .. } | Backend::Sparse { height, .. } => *height,
+        }
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub(crate) fn set_generation(&mut self, generation: usize) {
+        self.generation = generation;
+    }
+
+    /// The seed of the random soup the board was filled with, if known.
+    pub fn rng_seed(&self) -> Option<u64> {
+        self.rng_seed
+    }
+
+    pub fn set_rng_seed(&mut self, rng_seed: Option<u64>) {
+        self.rng_seed = rng_seed;
+    }
+
+    /// Coordinates of all alive cells on the plane of an unbounded engine, or `None` for the
+    /// bounded engines.
+    pub(crate) fn plane_cells(&self) -> Option<Vec<(i64, i64)>> {
+        match &self.backend {
+            Backend::HashLife { life, .. } => Some(life.cells()),
+            Backend::Sparse { life, .. } => Some(life.cells().collect()),
+            _ => None,
+        }
+    }
+
+    /// Sets a cell on the plane of an unbounded engine. Ignored by the bounded engines.
+    pub(crate) fn set_plane_alive(&mut self, x: i64, y: i64, alive: bool) {
+        match &mut self.backend {
+            Backend::HashLife { life, .. } => life.set_alive(x, y, alive),
+            Backend::Sparse { life, .. } => life.set_alive(x, y, alive),
+            _ => {}
+        }
+    }
+
+    /// Returns a dense copy of the current state, or of the window onto the plane.
+    pub fn to_grid(&self) -> Grid {
+        match &self.backend {
+            Backend::Dense(cells) => cells.clone(),
+            Backend::Packed(bits) => bits.to_grid(),
+            Backend::HashLife { life, width, height } => {
+                let mut cells = Grid::new(*width, *height);
+                life.export(&mut cells, self.viewport);
+                cells
+            }
+            Backend::Sparse { life, width, height } => {
+                let mut cells = Grid::new(*width, *height);
+                life.export(&mut cells, self.viewport);
+                cells
+            }
+        }
+    }
+
+    pub fn is_alive(&self, x: usize, y: usize) -> bool {
+        match &self.backend {
+            Backend::Dense(cells) => cells.is_alive(x, y),
+            Backend::Packed(bits) => bits.is_alive(x, y),
+            Backend::HashLife { life, .. } => life.is_alive(x as i64 + self.viewport.0, y as i64 + self.viewport.1),
+            Backend::Sparse { life, .. } => life.is_alive(x as i64 + self.viewport.0, y as i64 + self.viewport.1),
+        }
+    }
+
+    /// Rendered intensity of a cell: 0xff while alive, decaying after death.
+    pub fn fade(&self, x: usize, y: usize) -> u8 {
+        match &self.backend {
+            Backend::Dense(cells) => cells.fade(x, y),
+            _ => if self.is_alive(x, y) { 0xff } else { 0 },
+        }
+    }
+
+    pub fn set_alive(&mut self, x: usize, y: usize, alive: bool) {
+        match &mut self.backend {
+            Backend::Dense(cells) => cells.set_alive(x, y, alive),
+            Backend::Packed(bits) => bits.set_alive(x, y, alive),
+            Backend::HashLife { life, .. } => life.set_alive(x as i64 + self.viewport.0, y as i64 + self.viewport.1, alive),
+            Backend::Sparse { life, .. } => life.set_alive(x as i64 + self.viewport.0, y as i64 + self.viewport.1, alive),
+        }
+        self.set_age(x as i64, y as i64, alive);
+    }
+
+    /// Sets a cell given by a board position that may lie beyond the board. Such cells are on
+    /// the plane for the unbounded engines and are ignored by the bounded ones.
+    pub fn set_alive_at(&mut self, x: i64, y: i64, alive: bool) {
+        let (vx, vy) = self.viewport;
+        let (width, height) = (self.width() as i64, self.height() as i64);
+        match &mut self.backend {
+            Backend::HashLife { life, .. } => life.set_alive(x + vx, y + vy, alive),
+            Backend::Sparse { life, .. } => life.set_alive(x + vx, y + vy, alive),
+            _ if (0..width).contains(&x) && (0..height).contains(&y) => self.set_alive(x as usize, y as usize, alive),
+            _ => {}
+        }
+        self.set_age(x, y, alive);
+    }
+
+    /// Kills a cell and clears its fade trail, so it disappears at once instead of fading out.
+    pub fn erase(&mut self, x: usize, y: usize) {
+        match &mut self.backend {
+            Backend::Dense(cells) => cells.set(x, y, (0, 0)),
+            _ => self.set_alive(x, y, false),
+        }
+        self.set_age(x as i64, y as i64, false);
+    }
+
+    /// Starts or stops counting how many steps every board cell has been alive or dead, for
+    /// coloring cells by age. Counting costs a pass over the board after every step.
+    pub fn set_track_ages(&mut self, track: bool) {
+        if track != self.ages.is_some() {
+            self.ages = track.then(|| self.initial_ages());
+        }
+    }
+
+    pub fn tracks_ages(&self) -> bool {
+        self.ages.is_some()
+    }
+
+    /// How many steps a board cell has been alive, counting the step it was born in, or minus
+    /// how many steps it has been dead. Cells that have not been alive since ages started being
+    /// tracked, or since the board was replaced, are 0, and cells alive back then are 1.
+    ///
+    /// `None` when ages are not tracked or the cell is beyond the board. The hashlife engine
+    /// counts every jump as one step.
+    pub fn age(&self, x: usize, y: usize) -> Option<i16> {
+        let ages = self.ages.as_ref()?;
+        (x < self.width() && y < self.height()).then(|| ages[y * self.width() + x])
+    }
+
+    /// Like [`Universe::view`] for cell ages: every element stands for a `2^shift` x `2^shift`
+    /// square of cells and holds the age of the youngest cell in it, that is the most recently
+    /// born alive cell or, if none is alive, the most recently died one. Parts beyond the board
+    /// are 0. `None` when ages are not tracked.
+    pub fn view_ages(&self, x: i64, y: i64, width: usize, height: usize, shift: u32) -> Option<Vec<i16>> {
+        let ages = self.ages.as_ref()?;
+        // alive cells first, the youngest of them, then the most recently died
+        let rank = |age: i16| match age {
+            1.. => (2, -age),
+            ..=-1 => (1, age),
+            0 => (0, 0),
+        };
+        let mut view = vec![0; width * height];
+        let x_end = (x + ((width as i64) << shift)).min(self.width() as i64);
+        let y_end = (y + ((height as i64) << shift)).min(self.height() as i64);
+        for cy in y.max(0)..y_end {
+            for cx in x.max(0)..x_end {
+                let age = ages[cy as usize * self.width() + cx as usize];
+                let shown = &mut view[((cy - y) >> shift) as usize * width + ((cx - x) >> shift) as usize];
+                if rank(age) > rank(*shown) {
+                    *shown = age;
+                }
+            }
+        }
+        Some(view)
+    }
+
+    /// Restarts the ages, if tracked, from the current board.
+    fn reset_ages(&mut self) {
+        if self.ages.is_some() {
+            self.ages = Some(self.initial_ages());
+        }
+    }
+
+    /// Ages of a board that has just been put in place: 1 for alive cells, 0 for dead ones.
+    fn initial_ages(&self) -> Vec<i16> {
+        let width = self.width();
+        (0..width * self.height()).map(|i| self.is_alive(i % width, i / width) as i16).collect()
+    }
+
+    /// Ages every board cell by a step, if ages are tracked.
+    fn update_ages(&mut self) {
+        let Some(mut ages) = self.ages.take() else {
+            return;
+        };
+        let width = self.width();
+        for (i, age) in ages.iter_mut().enumerate() {
+            *age = match (self.is_alive(i % width, i / width), *age) {
+                (true, 1..) => age.saturating_add(1),
+                (true, _) => 1,
+                (false, 1..) => -1,
+                (false, 0) => 0,
+                (false, _) => age.saturating_sub(1),
+            };
+        }
+        self.ages = Some(ages);
+    }
+
+    /// Records an edited board cell as just born or long dead, if ages are tracked.
+    fn set_age(&mut self, x: i64, y: i64, alive: bool) {
+        let (width, height) = (self.width() as i64, self.height() as i64);
+        if let Some(ages) = &mut self.ages {
+            if (0..width).contains(&x) && (0..height).contains(&y) {
+                ages[(y * width + x) as usize] = alive as i16;
+            }
+        }
+    }
+
+    /// Copies a `width` x `height` region whose top-left corner is at board position `(x, y)`
+    /// into a grid, where every grid cell stands for a `2^shift` x `2^shift` square of cells. A
+    /// grid cell is alive if any of its cells is and takes the brightest fade among them.
+    ///
+    /// The region may extend beyond the board: those parts are dead on the bounded engines and
+    /// show the plane on the unbounded ones. Only the region is visited, or only the alive cells
+    /// on the unbounded engines, so this is suited to drawing a zoomed view of a large universe.
+    pub fn view(&self, x: i64, y: i64, width: usize, height: usize, shift: u32) -> Grid {
+        let mut view = Grid::new(width, height);
+        let offset = (self.viewport.0 + x, self.viewport.1 + y);
+        match &self.backend {
+            Backend::HashLife { life, .. } => life.export_scaled(&mut view, offset, shift),
+            Backend::Sparse { life, .. } => life.export_scaled(&mut view, offset, shift),
+            _ => {
+                let x_end = (x + ((width as i64) << shift)).min(self.width() as i64);
+                let y_end = (y + ((height as i64) << shift)).min(self.height() as i64);
+                for cy in y.max(0)..y_end {
+                    for cx in x.max(0)..x_end {
+                        let (vx, vy) = (((cx - x) >> shift) as usize, ((cy - y) >> shift) as usize);
+                        let (alive, fade) = view.get(vx, vy);
+                        let (cx, cy) = (cx as usize, cy as usize);
+                        view.set(vx, vy, (alive | self.is_alive(cx, cy) as u8, fade.max(self.fade(cx, cy))));
+                    }
+                }
+            }
+        }
+        view
+    }
+
+    /// Number of alive cells. For the unbounded engines this counts the whole plane, including
+    /// cells outside the board.
+    pub fn population(&self) -> usize {
+        match &self.backend {
+            Backend::Dense(cells) => cells.population(),
+            Backend::Packed(bits) => bits.population(),
+            Backend::HashLife { life, .. } => life.population() as usize,
+            Backend::Sparse { life, .. } => life.population(),
+        }
+    }
+
+    /// Smallest rectangle holding all alive cells, as `(min_x, min_y, max_x, max_y)` in board
+    /// coordinates, or `None` if every cell is dead. On the unbounded engines it covers the whole
+    /// plane, so it may extend beyond the board.
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let (vx, vy) = self.viewport;
+        let bounds = match &self.backend {
+            Backend::Dense(cells) => {
+                let alive = cells.rows().enumerate().flat_map(|(y, row)| {
+                    let first = row.iter().position(|cell| cell.0 == 1);
+                    let last = row.iter().rposition(|cell| cell.0 == 1);
+                    first.zip(last).map(|(first, last)| (first, y, last))
+                });
+                alive.fold(None, |bounds, (first, y, last)| {
+                    let (min_x, min_y, max_x, _) = bounds.unwrap_or((first, y, last, y));
+                    Some((min_x.min(first), min_y, max_x.max(last), y))
+                })
+            }
+            Backend::Packed(bits) => bits.bounding_box(),
+            Backend::HashLife { life, .. } => return life.bounding_box().map(|(x0, y0, x1, y1)| (x0 - vx, y0 - vy, x1 - vx, y1 - vy)),
+            Backend::Sparse { life, .. } => return life.bounding_box().map(|(x0, y0, x1, y1)| (x0 - vx, y0 - vy, x1 - vx, y1 - vy)),
+        };
+        bounds.map(|(x0, y0, x1, y1)| (x0 as i64, y0 as i64, x1 as i64, y1 as i64))
+    }
+
+    /// The cells born and died in the last step. `None` before the first step after seeding and
+    /// on the hashlife engine.
+    pub fn changes(&self) -> Option<Changes> {
+        self.changes
+    }
+
+    /// Measures the current generation: population, last changes and bounding box.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            generation: self.generation,
+            population: self.population(),
+            changes: self.changes,
+            bounds: self.bounding_box(),
+        }
+    }
+
+    /// A hash of which cells are alive, ignoring fade trails, for recognizing repeated states.
+    /// On the unbounded engines it covers the whole plane.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        match &self.backend {
+            Backend::Dense(cells) => {
+                let alive: Vec<u8> = cells.cells().iter().map(|cell| cell.0).collect();
+                hasher.write(&alive);
+            }
+            Backend::Packed(bits) => bits.words().hash(&mut hasher),
+            Backend::HashLife { life, .. } => hasher.write_u64(life.state_hash()),
+            Backend::Sparse { .. } => {
+                let mut cells = self.plane_cells().unwrap_or_default();
+                cells.sort_unstable();
+                cells.hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Sets the alive cells of `pattern` with its top-left corner at board position `(x, y)`.
+    /// Cells beyond the board are dropped on the bounded engines and placed on the plane on the
+    /// unbounded ones.
+    pub fn place(&mut self, pattern: &Pattern, x: usize, y: usize) {
+        let (vx, vy) = self.viewport;
+        let (width, height) = (self.width(), self.height());
+        for &(px, py) in pattern.cells() {
+            let (x, y) = (x + px, y + py);
+            match &mut self.backend {
+                Backend::HashLife { life, .. } => life.set_alive(x as i64 + vx, y as i64 + vy, true),
+                Backend::Sparse { life, .. } => life.set_alive(x as i64 + vx, y as i64 + vy, true),
+                _ if x < width && y < height => self.set_alive(x, y, true),
+                _ => {}
+            }
+            self.set_age(x as i64, y as i64, true);
+        }
+    }
+
+    /// Captures the alive cells as a pattern tagged with the rule and generation, cropped to
+    /// their bounding box. On the unbounded engines this is the whole plane, not only the board.
+    pub fn to_pattern(&self) -> Pattern {
+        let alive: Vec<(i64, i64)> = self.plane_cells().unwrap_or_else(|| {
+            let cells = (0..self.height()).flat_map(|y| (0..self.width()).map(move |x| (x, y)));
+            cells.filter(|&(x, y)| self.is_alive(x, y)).map(|(x, y)| (x as i64, y as i64)).collect()
+        });
+        let min_x = alive.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = alive.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        Pattern::from_cells(alive.into_iter().map(|(x, y)| ((x - min_x) as usize, (y - min_y) as usize)))
+            .with_rule(self.rule)
+            .with_comment(format!("generation {}", self.generation))
+    }
+
+    /// Captures the `width` x `height` region of the board starting at `(x, y)` as a pattern
+    /// tagged with the rule and generation. Parts of the region beyond the board are empty.
+    pub fn extract(&self, x: usize, y: usize, width: usize, height: usize) -> Pattern {
+        let x_end = x.saturating_add(width).min(self.width());
+        let y_end = y.saturating_add(height).min(self.height());
+        let cells = (y..y_end).flat_map(|cy| (x..x_end).map(move |cx| (cx, cy)));
+        let alive = cells.filter(|&(cx, cy)| self.is_alive(cx, cy)).map(|(cx, cy)| (cx - x, cy - y));
+        Pattern::from_cells(alive)
+            .with_size(width, height)
+            .with_rule(self.rule)
+            .with_comment(format!("generation {}", self.generation))
+    }
+
+    /// Fills the whole board with uniform random noise from a fresh random seed, every cell
+    /// alive with probability 0.5. The seed is available from [`Universe::rng_seed`].
+    pub fn seed(&mut self) {
+        self.seed_with(rand::random(), DEFAULT_DENSITY);
+    }
+
+    /// Fills the whole board with uniform random noise, every cell alive with probability
+    /// `density`. The same seed and density always produce the same soup.
+    pub fn seed_with(&mut self, rng_seed: u64, density: f64) {
+        self.seed_using(&Seeding::Uniform, rng_seed, density);
+    }
+
+    /// Replaces the board with a soup generated by the given strategy and restarts the generation
+    /// count. The same strategy, seed and density always produce the same soup.
+    pub fn seed_using(&mut self, seeding: &Seeding, rng_seed: u64, density: f64) {
+        let mut cells = Grid::new(self.width(), self.height());
+        seeding::fill(&mut cells, seeding, rng_seed, density);
+        let engine = self.engine();
+        self.backend = Backend::Dense(cells);
+        self.set_engine(engine);
+        self.rng_seed = Some(rng_seed);
+        self.generation = 0;
+        self.changes = None;
+        self.reset_ages();
+    }
+
+    /// Advances the universe by one generation, or `2^step_log` generations on the hashlife
+    /// engine. Returns `false` if nothing changed. Fails only on the hashlife engine, once the
+    /// pattern would spread beyond its coordinate range, leaving the universe as it was.
+    pub fn step(&mut self) -> Result<bool, PlaneOverflow> {
+        let (changes, has_changes, generations) = match &mut self.backend {
+            Backend::Dense(cells) => {
+                let changes = calculate_state(cells, &self.rule, self.topology, self.threads);
+                (Some(changes), changes.any(), 1)
+            }
+            Backend::Packed(bits) => {
+                let changes = bits.step(&self.rule, self.topology, self.threads);
+                (Some(changes), changes.any(), 1)
+            }
+            // hashlife only knows whether anything changed across its jump
+            Backend::HashLife { life, .. } => (None, life.step()?, 1 << life.step_log()),
+            Backend::Sparse { life, .. } => {
+                let changes = life.step();
+                (Some(changes), changes.any(), 1)
+            }
+        };
+        self.generation = self.generation.saturating_add(generations);
+        self.changes = changes;
+        self.update_ages();
+        Ok(has_changes)
+    }
+}
+
+fn calculate_state(cells: &mut Grid, rule: &Rule, topology: Topology, threads: usize) -> Changes {
+    let cells_original = cells.clone();
+    let width = cells.width();
+    let height = cells.height();
+    step_in_bands(cells.cells_mut(), width, height, threads, |rows, out| {
+        calculate_rows(&cells_original, rule, topology, rows, out)
+    })
+}
+
+/// Computes the next state of `rows` of `cells_original` into `out`, which holds exactly those rows.
+fn calculate_rows(cells_original: &Grid, rule: &Rule, topology: Topology, rows: Range<usize>, out: &mut [(u8, u8)]) -> Changes {
+    let width = cells_original.width();
+    let height = cells_original.height();
+    let mut changes = Changes::default();
+    let row = |y: usize| &cells_original.cells()[y * width..(y + 1) * width];
+    for (y, out_row) in rows.zip(out.chunks_exact_mut(width)) {
+        let border_row = y == 0 || y + 1 == height;
+        let (above, mid, below) = (row(y.saturating_sub(1)), row(y), row((y + 1).min(height - 1)));
+        for (x, cell) in out_row.iter_mut().enumerate() {
+            let alive_neighbors = if border_row || x == 0 || x + 1 == width {
+                // only cells on the edge have neighbors that the topology has to resolve
+                let alive = |dx: isize, dy: isize| {
+                    topology
+                        .resolve(x as isize + dx, y as isize + dy, width, height)
+                        .map_or(0, |(x, y)| cells_original.get(x, y).0)
+                };
+                alive(-1, -1) + alive(0, -1) + alive(1, -1) + alive(-1, 0) + alive(1, 0) + alive(-1, 1) + alive(0, 1) + alive(1, 1)
+            } else {
+                let top_left = above[x - 1].0;
+                let top_mid = above[x].0;
+                let top_right = above[x + 1].0;
+                let mid_left = mid[x - 1].0;
+                let mid_right = mid[x + 1].0;
+                let bottom_left = below[x - 1].0;
+                let bottom_mid = below[x].0;
+                let bottom_right = below[x + 1].0;
+                top_left + top_mid + top_right + mid_left + mid_right + bottom_left + bottom_mid + bottom_right
+            };
+            let (state, fade) = cells_original.get(x, y);
+            let faded = (fade as f32 * 0.95) as u8;
+            match (state == 1, rule.next_state(state == 1, alive_neighbors)) {
+                // survival
+                (true, true) => *cell = (1, 0xff),
+                // underpopulation or overpopulation
+                (true, false) => {
+                    *cell = (0, faded);
+                    changes.deaths += 1;
+                }
+                // reproduction
+                (false, true) => {
+                    *cell = (1, 0xff);
+                    changes.births += 1;
+                }
+                (false, false) => *cell = (0, faded),
+            }
+        }
+    }
+
+    changes
+}